@@ -2,27 +2,106 @@ use krabmaga::engine::location::Int2D;
 
 use super::misc::misc_func::Reset;
 
+/// What killed or injured an evacuee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeathCause {
+    /// Caught by the fire
+    Fire,
+    /// Crushed by the pressure of the crowd
+    Crush,
+    /// Hurt in an argument over a cell
+    Argument,
+    /// Hurt while being pushed out of a cell
+    Push,
+}
+
+pub const CAUSES: [DeathCause; 4] = [
+    DeathCause::Fire,
+    DeathCause::Crush,
+    DeathCause::Argument,
+    DeathCause::Push,
+];
+
+impl DeathCause {
+    pub fn index(&self) -> usize {
+        match self {
+            DeathCause::Fire => 0,
+            DeathCause::Crush => 1,
+            DeathCause::Argument => 2,
+            DeathCause::Push => 3,
+        }
+    }
+}
+
 pub trait DeathHandler: Reset {
-    fn update_death(&mut self, loc: Int2D);
+    fn update_death(&mut self, loc: Int2D, cause: DeathCause);
+
+    /// Record an evacuee hurt by `cause` that is still able to move
+    fn update_injury(&mut self, loc: Int2D, cause: DeathCause);
 
+    /// Total number of dead evacuees
     fn get_dead(&self) -> usize;
+
+    fn get_dead_by(&self, cause: DeathCause) -> usize;
+
+    fn get_injured_by(&self, cause: DeathCause) -> usize;
 }
 
 #[derive(Default)]
-pub struct Announcer(usize);
+pub struct Announcer {
+    dead: [usize; CAUSES.len()],
+    injured: [usize; CAUSES.len()],
+}
 
 impl Reset for Announcer {
     fn reset(&mut self) {
-        self.0 = 0;
+        *self = Self::default();
     }
 }
 
 impl DeathHandler for Announcer {
-    fn update_death(&mut self, _loc: Int2D) {
-        self.0 += 1;
+    fn update_death(&mut self, _loc: Int2D, cause: DeathCause) {
+        self.dead[cause.index()] += 1;
+    }
+
+    fn update_injury(&mut self, _loc: Int2D, cause: DeathCause) {
+        self.injured[cause.index()] += 1;
     }
 
     fn get_dead(&self) -> usize {
-        self.0
+        self.dead.iter().sum()
+    }
+
+    fn get_dead_by(&self, cause: DeathCause) -> usize {
+        self.dead[cause.index()]
+    }
+
+    fn get_injured_by(&self, cause: DeathCause) -> usize {
+        self.injured[cause.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_causes() {
+        let loc = Int2D { x: 0, y: 0 };
+        let mut announcer = Announcer::default();
+        announcer.update_death(loc, DeathCause::Fire);
+        announcer.update_death(loc, DeathCause::Crush);
+        announcer.update_death(loc, DeathCause::Crush);
+        announcer.update_injury(loc, DeathCause::Crush);
+        announcer.update_injury(loc, DeathCause::Push);
+        assert_eq!(announcer.get_dead(), 3);
+        assert_eq!(announcer.get_dead_by(DeathCause::Fire), 1);
+        assert_eq!(announcer.get_dead_by(DeathCause::Crush), 2);
+        assert_eq!(announcer.get_injured_by(DeathCause::Crush), 1);
+        assert_eq!(announcer.get_injured_by(DeathCause::Fire), 0);
+        assert_eq!(announcer.get_injured_by(DeathCause::Push), 1);
+        assert_eq!(announcer.get_injured_by(DeathCause::Argument), 0);
+        announcer.reset();
+        assert_eq!(announcer.get_dead(), 0);
     }
 }
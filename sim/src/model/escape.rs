@@ -11,6 +11,11 @@ pub trait EscapeHandler<T>: Reset {
     fn get_escaped(&self) -> Vec<T>;
     fn get_escaped_number(&self) -> usize;
     fn is_exit(&self, loc: &Loc) -> bool;
+    fn exit(&self) -> Loc;
+    /// Every exit of the room
+    fn exits(&self) -> Vec<Loc> {
+        vec![self.exit()]
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -63,6 +68,10 @@ impl EscapeHandler<EvacTime> for TimeEscape {
     fn get_escaped_number(&self) -> usize {
         self.escaped_evac.len()
     }
+
+    fn exit(&self) -> Loc {
+        self.exit.into()
+    }
 }
 
 #[cfg(test)]
@@ -104,6 +113,7 @@ mod tests {
             y: 30,
             pr_c: rng.gen(),
             pr_d: rng.gen(),
+            ..Default::default()
         };
         time_escape.escaped(cell.clone(), step);
         assert_eq!(time_escape.escaped_evac[0].loc, cell);
@@ -0,0 +1,239 @@
+use krabmaga::HashMap;
+use serde::Deserialize;
+
+use crate::model::misc::misc_func::Loc;
+
+use super::evacuee_cell::{EvacueeCell, INJURED_SPEED_FACTOR};
+
+/// Cooperative evacuees helping injured or slow neighbours out
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct AssistPolicy {
+    /// Scale of the cooperation probability giving the chance to help a neighbour in need
+    #[serde(default = "default_willingness")]
+    pub willingness: f32,
+    /// Evacuees slower than this need help even when they are not injured
+    #[serde(default)]
+    pub slow_speed: f32,
+    /// Fraction of the helper speed kept while moving with the helped evacuee, above zero
+    pub speed_factor: f32,
+}
+
+fn default_willingness() -> f32 {
+    1.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Pair {
+    helper: usize,
+    /// Cell of the helper after the last update, the helped evacuee steps into it when it is left
+    helper_loc: Loc,
+    /// Speed of the helped evacuee before it was paired
+    helped_speed: f32,
+    /// Whether the helped evacuee was already injured when it was paired
+    helped_injured: bool,
+}
+
+/// Pair that no longer moves together because one of them is gone or they were separated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Released {
+    pub helper: usize,
+    pub helped: usize,
+}
+
+/// Evacuees moving together with the one helping them, indexed by the id of the helped evacuee
+#[derive(Debug, Clone)]
+pub struct Assistance {
+    pub policy: AssistPolicy,
+    pairs: HashMap<usize, Pair>,
+    /// Helped evacuees that got out together with their helper since the start
+    pub rescues: u64,
+}
+
+impl Assistance {
+    pub fn new(policy: AssistPolicy) -> Self {
+        Self {
+            policy,
+            pairs: HashMap::new(),
+            rescues: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.pairs.clear();
+        self.rescues = 0;
+    }
+
+    /// Number of evacuees currently being helped
+    pub fn assisted(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Whether `id` is helping or being helped
+    pub fn is_paired(&self, id: usize) -> bool {
+        self.pairs.contains_key(&id) || self.pairs.values().any(|p| p.helper == id)
+    }
+
+    /// Whether `evac` cannot get out on its own and nobody helps it yet
+    pub fn needs_help(&self, evac: &EvacueeCell) -> bool {
+        !self.is_paired(evac.id) && (evac.injured || evac.speed < self.policy.slow_speed)
+    }
+
+    /// Chance of `helper` helping a neighbour in need
+    pub fn help_probability(&self, helper: &EvacueeCell) -> f32 {
+        (helper.pr_c * self.policy.willingness).clamp(0., 1.)
+    }
+
+    /// Pair up `helper` and `helped`. The helper slows down to the speed factor
+    /// and the helped evacuee stops moving on its own
+    pub fn pair(&mut self, helper: &mut EvacueeCell, helped: &mut EvacueeCell) {
+        self.pairs.insert(
+            helped.id,
+            Pair {
+                helper: helper.id,
+                helper_loc: Loc(helper.x, helper.y),
+                helped_speed: helped.speed,
+                helped_injured: helped.injured,
+            },
+        );
+        helper.speed *= self.policy.speed_factor;
+        helped.speed = 0.;
+        helped.move_budget = 0.;
+    }
+
+    /// Move every helped evacuee into the cell its helper left if it is still free.
+    /// Pairs missing one of the evacuees or no longer adjacent are released
+    /// and the speed of the ones left is restored, keeping the injuries taken while paired
+    pub fn follow(&mut self, evacuees: &mut [EvacueeCell]) -> Vec<Released> {
+        let index: HashMap<usize, usize> = evacuees
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.id, i))
+            .collect();
+        let mut released = vec![];
+        for (helped, pair) in self.pairs.iter_mut() {
+            let (Some(&i), Some(&j)) = (index.get(&pair.helper), index.get(helped)) else {
+                released.push(Released {
+                    helper: pair.helper,
+                    helped: *helped,
+                });
+                continue;
+            };
+            let now = Loc(evacuees[i].x, evacuees[i].y);
+            let left = pair.helper_loc;
+            if now != left && !evacuees.iter().any(|e| Loc(e.x, e.y) == left) {
+                evacuees[j].x = left.0;
+                evacuees[j].y = left.1;
+            }
+            pair.helper_loc = now;
+            let Loc(x, y) = now;
+            if (evacuees[j].x - x).abs() > 1 || (evacuees[j].y - y).abs() > 1 {
+                released.push(Released {
+                    helper: pair.helper,
+                    helped: *helped,
+                });
+            }
+        }
+        for r in released.iter() {
+            let pair = self.pairs.remove(&r.helped).unwrap();
+            for e in evacuees.iter_mut() {
+                if e.id == r.helper {
+                    e.speed /= self.policy.speed_factor;
+                } else if e.id == r.helped {
+                    e.speed = pair.helped_speed;
+                    if e.injured && !pair.helped_injured {
+                        e.speed *= INJURED_SPEED_FACTOR;
+                    }
+                }
+            }
+        }
+        released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evac(id: usize, x: i32, y: i32, speed: f32) -> EvacueeCell {
+        EvacueeCell {
+            id,
+            x,
+            y,
+            speed,
+            ..Default::default()
+        }
+    }
+
+    fn assistance() -> Assistance {
+        Assistance::new(AssistPolicy {
+            willingness: 1.,
+            slow_speed: 0.5,
+            speed_factor: 0.5,
+        })
+    }
+
+    #[test]
+    fn test_needs_help() {
+        let mut a = assistance();
+        let mut injured = evac(1, 0, 0, 1.);
+        injured.injure();
+        assert!(a.needs_help(&injured));
+        assert!(a.needs_help(&evac(2, 0, 0, 0.25)));
+        assert!(!a.needs_help(&evac(3, 0, 0, 1.)));
+        let mut helper = evac(4, 1, 0, 1.);
+        a.pair(&mut helper, &mut injured);
+        assert!(!a.needs_help(&injured));
+        assert!(a.is_paired(4));
+        assert_eq!(helper.speed, 0.5);
+        assert_eq!(injured.speed, 0.);
+    }
+
+    #[test]
+    fn test_follow_helper() {
+        let mut a = assistance();
+        let mut evacuees = [evac(1, 5, 5, 1.), evac(2, 5, 6, 0.5)];
+        let [helper, helped] = &mut evacuees;
+        a.pair(helper, helped);
+        evacuees[0].y = 4;
+        assert!(a.follow(&mut evacuees).is_empty());
+        assert_eq!((evacuees[1].x, evacuees[1].y), (5, 5));
+        assert_eq!(a.assisted(), 1);
+    }
+
+    #[test]
+    fn test_release() {
+        let mut a = assistance();
+        let mut evacuees = vec![evac(1, 5, 5, 1.), evac(2, 5, 6, 0.5)];
+        let [helper, helped] = evacuees.as_mut_slice() else {
+            unreachable!()
+        };
+        a.pair(helper, helped);
+        // The helper escaped
+        evacuees.remove(0);
+        let released = a.follow(&mut evacuees);
+        assert_eq!(
+            released,
+            vec![Released {
+                helper: 1,
+                helped: 2
+            }]
+        );
+        assert_eq!(evacuees[0].speed, 0.5);
+        assert_eq!(a.assisted(), 0);
+    }
+
+    #[test]
+    fn test_release_keeps_injuries() {
+        let mut a = assistance();
+        let mut evacuees = [evac(1, 5, 5, 1.), evac(2, 5, 6, 0.5)];
+        let [helper, helped] = &mut evacuees;
+        a.pair(helper, helped);
+        evacuees[0].injure();
+        evacuees[1].injure();
+        // Separated
+        evacuees[1].y = 9;
+        assert_eq!(a.follow(&mut evacuees).len(), 1);
+        assert_eq!(evacuees[0].speed, INJURED_SPEED_FACTOR);
+        assert_eq!(evacuees[1].speed, 0.5 * INJURED_SPEED_FACTOR);
+    }
+}
@@ -0,0 +1,174 @@
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use rand_derive2::RandGen;
+use serde::Deserialize;
+
+use super::strategy::Strategy;
+
+/// Distance to the exit within which free-riders become competitive
+pub const DEFAULT_FREE_RIDER_RADIUS: f32 = 5.;
+
+/// Behavioural type of an evacuee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, RandGen)]
+pub enum Behaviour {
+    /// Learns its strategy from the contests it takes part in
+    #[default]
+    Adaptive,
+    /// Always cooperates and yields the cell to anyone else
+    Altruist,
+    /// Copies the majority strategy of its Moore neighbours
+    Follower,
+    /// Competitive near the exit, cooperative elsewhere
+    FreeRider,
+    /// Trained fire warden, cooperates and guides the evacuees around it
+    Warden,
+    /// Responder walking towards the fire, competes for the cells on its way
+    Firefighter,
+}
+
+pub const BEHAVIOURS: [Behaviour; 6] = [
+    Behaviour::Adaptive,
+    Behaviour::Altruist,
+    Behaviour::Follower,
+    Behaviour::FreeRider,
+    Behaviour::Warden,
+    Behaviour::Firefighter,
+];
+
+impl Behaviour {
+    /// Position of the behaviour in `BEHAVIOURS`
+    #[inline]
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Whether the strategy is learnt from the contests
+    #[inline]
+    pub fn adaptive(&self) -> bool {
+        *self == Behaviour::Adaptive
+    }
+
+    /// Strategy played in a contest.
+    /// `current` - Strategy held by the evacuee
+    /// `neighbours` - Strategies of the Moore neighbours
+    /// `near_exit` - Whether the evacuee is within the free-rider radius of the exit
+    pub fn contest_strategy(
+        &self,
+        current: Strategy,
+        neighbours: &[Strategy],
+        near_exit: bool,
+    ) -> Strategy {
+        match self {
+            Behaviour::Adaptive => current,
+            Behaviour::Altruist | Behaviour::Warden => Strategy::Cooperative,
+            Behaviour::Follower => majority(neighbours).unwrap_or(current),
+            Behaviour::FreeRider if near_exit => Strategy::Competitive,
+            Behaviour::FreeRider => Strategy::Cooperative,
+            Behaviour::Firefighter => Strategy::Competitive,
+        }
+    }
+}
+
+/// Most common strategy, `None` on a tie
+fn majority(strategies: &[Strategy]) -> Option<Strategy> {
+    let coops = strategies
+        .iter()
+        .filter(|s| **s == Strategy::Cooperative)
+        .count();
+    match (2 * coops).cmp(&strategies.len()) {
+        std::cmp::Ordering::Greater => Some(Strategy::Cooperative),
+        std::cmp::Ordering::Less => Some(Strategy::Competitive),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+/// Share of the population with a given behaviour
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct BehaviourShare {
+    pub behaviour: Behaviour,
+    /// Relative share of the population, does not need to sum up to one
+    pub proportion: f32,
+}
+
+/// Sample the behaviour of a new evacuee, everyone is adaptive if no shares are given
+pub fn sample_behaviour(shares: &[BehaviourShare], rng: &mut dyn RngCore) -> Behaviour {
+    if shares.is_empty() {
+        return Behaviour::Adaptive;
+    }
+    WeightedIndex::new(shares.iter().map(|c| c.proportion))
+        .map(|dist| shares[dist.sample(rng)].behaviour)
+        .expect("Behaviour proportions are not valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn test_indices_match() {
+        for (i, b) in BEHAVIOURS.iter().enumerate() {
+            assert_eq!(b.index(), i);
+        }
+    }
+
+    #[test]
+    fn test_fixed_types() {
+        use Strategy::*;
+        let neigh = [Competitive, Competitive, Cooperative];
+        assert_eq!(
+            Behaviour::Altruist.contest_strategy(Competitive, &neigh, true),
+            Cooperative
+        );
+        assert_eq!(
+            Behaviour::Follower.contest_strategy(Cooperative, &neigh, false),
+            Competitive
+        );
+        // ties and empty neighbourhoods keep the current strategy
+        assert_eq!(
+            Behaviour::Follower.contest_strategy(Cooperative, &neigh[1..], false),
+            Cooperative
+        );
+        assert_eq!(
+            Behaviour::Follower.contest_strategy(Competitive, &[], false),
+            Competitive
+        );
+        assert_eq!(
+            Behaviour::FreeRider.contest_strategy(Cooperative, &[], true),
+            Competitive
+        );
+        assert_eq!(
+            Behaviour::FreeRider.contest_strategy(Competitive, &[], false),
+            Cooperative
+        );
+        assert_eq!(
+            Behaviour::Adaptive.contest_strategy(Competitive, &[], false),
+            Competitive
+        );
+        assert_eq!(
+            Behaviour::Warden.contest_strategy(Competitive, &neigh, true),
+            Cooperative
+        );
+        assert_eq!(
+            Behaviour::Firefighter.contest_strategy(Cooperative, &neigh, false),
+            Competitive
+        );
+    }
+
+    #[test]
+    fn test_sample_behaviour() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        assert_eq!(sample_behaviour(&[], &mut rng), Behaviour::Adaptive);
+        let shares = [
+            BehaviourShare {
+                behaviour: Behaviour::Altruist,
+                proportion: 1.,
+            },
+            BehaviourShare {
+                behaviour: Behaviour::FreeRider,
+                proportion: 0.,
+            },
+        ];
+        assert!((0..50).all(|_| sample_behaviour(&shares, &mut rng) == Behaviour::Altruist));
+    }
+}
@@ -0,0 +1,160 @@
+use mockall::automock;
+
+/// Area of a single cell in m^2, a cell fits one person (0.4m x 0.4m)
+pub const DEFAULT_CELL_AREA: f32 = 0.16;
+
+#[automock]
+/// Speed-density relation (fundamental diagram) of pedestrian flow
+pub trait SpeedDensity {
+    /// Fraction of the free walking speed at the given density in persons per m^2
+    fn speed_factor(&self, density: f32) -> f32;
+}
+
+/// Weidmann's relation
+/// $$
+/// v(rho) = v_0 (1 - exp(-gamma (1/rho - 1/rho_max)))
+/// $$
+#[derive(Debug, Clone)]
+pub struct Weidmann {
+    pub gamma: f32,
+    /// Density at which movement stops
+    pub rho_max: f32,
+}
+
+impl Default for Weidmann {
+    fn default() -> Self {
+        Self {
+            gamma: 1.913,
+            rho_max: 5.4,
+        }
+    }
+}
+
+impl SpeedDensity for Weidmann {
+    fn speed_factor(&self, density: f32) -> f32 {
+        if density <= 0. {
+            return 1.;
+        }
+        (1. - (-self.gamma * (density.recip() - self.rho_max.recip())).exp()).clamp(0., 1.)
+    }
+}
+
+/// Linear (Greenshields) relation
+/// $$
+/// v(rho) = v_0 (1 - rho / rho_max)
+/// $$
+#[derive(Debug, Clone)]
+pub struct LinearDensity {
+    pub rho_max: f32,
+}
+
+impl Default for LinearDensity {
+    fn default() -> Self {
+        Self { rho_max: 5.4 }
+    }
+}
+
+impl SpeedDensity for LinearDensity {
+    fn speed_factor(&self, density: f32) -> f32 {
+        (1. - density / self.rho_max).clamp(0., 1.)
+    }
+}
+
+/// Reduce the chance of an evacuee moving based on the crowd density around him
+pub struct DensitySpeed {
+    pub relation: Box<dyn SpeedDensity + Send>,
+    /// Half width of the square window the density is measured on
+    pub radius: i32,
+    /// Area of a cell in m^2
+    pub cell_area: f32,
+}
+
+impl Default for DensitySpeed {
+    fn default() -> Self {
+        Self {
+            relation: Box::new(Weidmann::default()),
+            radius: 1,
+            cell_area: DEFAULT_CELL_AREA,
+        }
+    }
+}
+
+impl DensitySpeed {
+    /// Density in persons per m^2 given the occupied cells of a window of `window` cells
+    #[inline]
+    pub fn density(&self, occupied: usize, window: usize) -> f32 {
+        if window == 0 {
+            return 0.;
+        }
+        occupied as f32 / (window as f32 * self.cell_area)
+    }
+
+    /// Probability of an evacuee moving this step
+    pub fn move_probability(&self, occupied: usize, window: usize) -> f32 {
+        self.relation
+            .speed_factor(self.density(occupied, window))
+            .clamp(0., 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn weidmann_is_decreasing(a in 0.01f32..5.4, b in 0.01f32..5.4) {
+            let w = Weidmann::default();
+            let (lo, hi) = if a < b { (a, b) } else { (b, a) };
+            prop_assert!(w.speed_factor(lo) >= w.speed_factor(hi));
+        }
+
+        #[test]
+        fn move_probability_is_bounded(occupied in 0usize..50, window in 0usize..50) {
+            let density = DensitySpeed {
+                relation: Box::new(LinearDensity::default()),
+                ..Default::default()
+            };
+            let p = density.move_probability(occupied, window);
+            prop_assert!((0. ..=1.).contains(&p));
+        }
+    }
+
+    #[test]
+    fn weidmann_limits() {
+        let w = Weidmann::default();
+        assert_relative_eq!(w.speed_factor(0.), 1.);
+        assert!(w.speed_factor(0.1) > 0.99);
+        assert_relative_eq!(w.speed_factor(w.rho_max), 0.);
+        assert_relative_eq!(w.speed_factor(10.), 0.);
+    }
+
+    #[test]
+    fn linear_limits() {
+        let l = LinearDensity { rho_max: 4. };
+        assert_relative_eq!(l.speed_factor(0.), 1.);
+        assert_relative_eq!(l.speed_factor(2.), 0.5);
+        assert_relative_eq!(l.speed_factor(8.), 0.);
+    }
+
+    #[test]
+    fn density_from_window() {
+        let density = DensitySpeed::default();
+        assert_relative_eq!(density.density(0, 9), 0.);
+        assert_relative_eq!(density.density(9, 9), DEFAULT_CELL_AREA.recip());
+        assert_relative_eq!(density.density(3, 0), 0.);
+    }
+
+    #[test]
+    fn relation_is_used_for_probability() {
+        let mut relation = MockSpeedDensity::new();
+        relation.expect_speed_factor().once().return_const(0.3);
+        let density = DensitySpeed {
+            relation: Box::new(relation),
+            ..Default::default()
+        };
+        assert_relative_eq!(density.move_probability(4, 9), 0.3);
+    }
+}
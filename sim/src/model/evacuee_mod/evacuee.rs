@@ -1,12 +1,15 @@
 use super::fire_influence::fire_influence::FireInfluence;
+use super::learning::{BushMosteller, LearningRule, SharedLearning};
+use super::mixed::update_level;
 use super::strategy::Strategy;
+use super::warden::Guide;
 use super::{evacuee_cell::EvacueeCell, static_influence::StaticInfluence};
 use crate::model::misc::misc_func::Loc;
 use crate::model::state::CellGrid;
 use itertools::Itertools;
 use krabmaga::engine::agent::Agent;
 use krabmaga::engine::location::Int2D;
-use krabmaga::{rand as krand, Rng};
+use krabmaga::rand as krand;
 use krand::rngs::StdRng;
 use krand::{RngCore, SeedableRng};
 
@@ -18,6 +21,8 @@ pub struct EvacueeAgent {
     pub id: usize,
     pub lc: f32,
     pub ld: f32,
+    /// Learning rule of every agent class, Bush-Mosteller with `lc` and `ld` for classes without one
+    pub learning: Vec<SharedLearning>,
 }
 
 /// Implementation of constructor methods
@@ -28,52 +33,61 @@ impl EvacueeAgent {
     /// force_influence = s_effect * static_influence +  d_effect * dynamic influence
     /// ```
     /// The higher the dynamic_influence, the higher the force incluence.
+    /// Evacuees directed by a warden have the extra distance to its chosen exit added to the force, weighted by the guidance.
+    /// Every neighbour is paired with the cost of stepping into it, the weight of a cell is divided by its cost
+    /// so that longer (diagonal) steps are less likely.
     /// To get probability distribution use the softmax function across all the neighbours
     pub fn calculate_probabilities(
         &self,
-        neigh: &[Loc],
+        neigh: &[(Loc, f32)],
         static_st: &dyn StaticInfluence,
         fire_infl: &FireInfluence,
+        guide: Option<Guide>,
     ) -> Vec<f32> {
         let all = neigh
             .iter()
-            .map(|cs| {
+            .map(|(cs, cost)| {
                 let d = fire_infl.get_movement_influence(&cs);
                 let s = static_st.static_influence(&Int2D::from(*cs));
-                let result = -s + d;
+                let g = guide.map_or(0., |g| g.term(*cs, neigh));
+                let result = -s + d + g;
                 let sign = result.signum();
-                (result.abs().sqrt() * sign).exp() // Use sqrt since long distances can skyrocket the exponetial value
+                (result.abs().sqrt() * sign).exp() / cost // Use sqrt since long distances can skyrocket the exponetial value
             })
             .collect_vec();
         let s: f32 = all.iter().sum();
         all.into_iter().map(|el| el / s).collect_vec()
     }
 
-    pub fn calculate_strategies(&self, evac: &mut EvacueeCell, rng: &mut dyn RngCore, stim: f32) {
-        match evac.strategy {
-            Strategy::Competitive => {
-                // ADOPT FOR COOP
-                evac.pr_d = calc_prob(evac.pr_d, self.ld, stim);
-                if !rng.gen_bool(evac.pr_d as f64) {
-                    evac.strategy = Strategy::Cooperative;
-                }
-            }
-            Strategy::Cooperative => {
-                // ADOPT FOR COOP
-                evac.pr_c = calc_prob(evac.pr_c, self.lc, stim);
-                if !rng.gen_bool(evac.pr_c as f64) {
-                    evac.strategy = Strategy::Competitive;
-                }
+    /// Update the strategy of `evac` with the learning rule of its class.
+    /// `peers` - last payoff and strategy of the neighbours of `evac`
+    pub fn calculate_strategies(
+        &self,
+        evac: &mut EvacueeCell,
+        rng: &mut dyn RngCore,
+        stim: f32,
+        peers: &[(f32, Strategy)],
+    ) {
+        match self.learning.get(evac.class) {
+            Some(rule) => rule.update(evac, stim, peers, rng),
+            None => BushMosteller {
+                lc: self.lc,
+                ld: self.ld,
             }
+            .update(evac, stim, peers, rng),
         }
     }
-}
 
-fn calc_prob(prob: f32, learning: f32, stim: f32) -> f32 {
-    if stim.is_sign_positive() {
-        prob + (1. - prob) * learning * stim
-    } else {
-        prob * (1. + learning * stim)
+    /// Update the cooperation level of `evac` after playing its current strategy,
+    /// `lc` and `ld` are the learning rates after cooperating and competing respectively
+    pub fn calculate_level(&self, evac: &mut EvacueeCell, stim: f32) {
+        let rate = match evac.strategy {
+            Strategy::Cooperative => self.lc,
+            Strategy::Competitive => self.ld,
+        };
+        evac.coop_level = update_level(evac.coop_level, evac.strategy, rate, stim);
+        evac.pr_c = evac.coop_level;
+        evac.pr_d = 1. - evac.coop_level;
     }
 }
 
@@ -89,6 +103,7 @@ impl Agent for EvacueeAgent {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::evacuee_mod::learning::calc_prob;
     use crate::model::{
         evacuee_mod::{
             fire_influence::{
@@ -112,7 +127,8 @@ mod tests {
                     ((*x - exit.0).abs() as f32 + (*y - exit.1).abs() as f32).ln_1p()
                 });
             let fire_infl = FireInfluence::default();
-            let from_evac = evac.calculate_probabilities(&arr, &stat, &fire_infl);
+            let neigh = arr.iter().map(|l| (*l, 1.)).collect_vec();
+            let from_evac = evac.calculate_probabilities(&neigh, &stat, &fire_infl, None);
             let from_arr = arr.iter().map(|l| {
                 let s = stat.static_influence(&Into::into(*l));
                 let d = fire_infl.get_movement_influence(&Into::into(*l));
@@ -126,6 +142,42 @@ mod tests {
             prop_assert!(x);
         }
 
+        #[test]
+        fn test_probabilities_normalised(arr in proptest::collection::vec(((0i32..50,0i32..50).prop_map(|(x,y)| Loc(x,y)), prop_oneof![Just(1f32), Just(std::f32::consts::SQRT_2)]), 1..8), exit in (0i32..50, 0i32..50).prop_map(|(x,y)| Loc(x,y))) {
+            let evac = EvacueeAgent::default();
+            let mut stat = MockStaticInfluence::new();
+            stat.expect_static_influence()
+                .returning(move |Int2D {x,y} : &Int2D| {
+                    let exit = exit;
+                    ((*x - exit.0).abs() as f32 + (*y - exit.1).abs() as f32).ln_1p()
+                });
+            let fire_infl = FireInfluence::default();
+            let probs = evac.calculate_probabilities(&arr, &stat, &fire_infl, None);
+            prop_assert_eq!(probs.len(), arr.len());
+            prop_assert!(probs.iter().all(|p| *p >= 0. && *p <= 1.));
+            prop_assert!((probs.iter().sum::<f32>() - 1.).abs() < 1e-4);
+        }
+
+        #[test]
+        fn test_diagonal_less_likely(loc in (0i32..50,0i32..50).prop_map(|(x,y)| Loc(x,y))) {
+            let evac = EvacueeAgent::default();
+            let stat = crate::model::evacuee_mod::static_influence::ConstantInfluence;
+            let fire_infl = FireInfluence::default();
+            let probs = evac.calculate_probabilities(&[(loc, 1.), (loc, std::f32::consts::SQRT_2)], &stat, &fire_infl, None);
+            prop_assert!((probs[0] / probs[1] - std::f32::consts::SQRT_2).abs() < 1e-5);
+        }
+
+        #[test]
+        fn test_guided_towards_exit(x in 1i32..49, y in 1i32..49, weight in 0.1f32..5.) {
+            let evac = EvacueeAgent::default();
+            let stat = crate::model::evacuee_mod::static_influence::ConstantInfluence;
+            let fire_infl = FireInfluence::default();
+            let neigh = [(Loc(x - 1, y), 1.), (Loc(x + 1, y), 1.)];
+            let guide = Guide { exit: Loc(0, y), weight };
+            let probs = evac.calculate_probabilities(&neigh, &stat, &fire_infl, Some(guide));
+            prop_assert!(probs[0] > probs[1]);
+        }
+
         #[test]
         fn test_probability(lc in 0.0f32..1.0f32, ld in 0.0f32..1.0f32, prob_c in 0.0f32..1.0f32, prob_d in 0.0f32..1.0f32, st in -1.0f32..=1.0f32, strat in 0..=1u32) {
             let mut strategy = if strat == 0 { crate::model::evacuee_mod::strategy::Strategy::Cooperative } else { crate::model::evacuee_mod::strategy::Strategy::Competitive };
@@ -133,7 +185,8 @@ mod tests {
             let evac = EvacueeAgent {
                 id : 1,
                 lc,
-                ld
+                ld,
+                ..Default::default()
             };
 
             let mut evac_cell = EvacueeCell {
@@ -142,9 +195,10 @@ mod tests {
                 y : 0,
                 pr_c : prob_c,
                 pr_d : prob_d,
+                ..Default::default()
             };
 
-            evac.calculate_strategies(&mut evac_cell, &mut rng, st);
+            evac.calculate_strategies(&mut evac_cell, &mut rng, st, &[]);
             let mut pr_d = prob_d;
             let mut pr_c = prob_c;
             let mut rng = StdRng::seed_from_u64(50);
@@ -1,15 +1,127 @@
 use serde::Deserialize;
 
-use crate::model::evacuee_mod::strategy::Strategy;
+use crate::model::evacuee_mod::{behaviour::Behaviour, strategy::Strategy};
+
+/// Fraction of the speed kept after an injury
+pub const INJURED_SPEED_FACTOR: f32 = 0.5;
+
+fn default_speed() -> f32 {
+    1.
+}
+
+pub fn default_strength() -> f32 {
+    1.
+}
+
+fn default_level() -> f32 {
+    0.5
+}
 
 #[derive(Debug, Clone, Copy, Deserialize, rand_derive2::RandGen)]
 pub struct EvacueeCell {
+    /// Persistent identifier, unique within a simulation
+    #[serde(default)]
+    pub id: usize,
     pub strategy: Strategy,
     pub x: i32,
     pub y: i32,
     /// probabillity of adopting strategy comp
     pub pr_c: f32,
     pub pr_d: f32,
+    /// Index of the agent class the evacuee belongs to
+    #[serde(default)]
+    pub class: usize,
+    /// Cells covered per tick. Agents faster than 1 take several sub-steps, slower agents skip ticks
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    /// Movement accumulated but not yet spent, a sub-step costs one cell
+    #[serde(default)]
+    pub move_budget: f32,
+    /// Relative weight of the evacuee when winning an argument
+    #[serde(default = "default_strength")]
+    pub strength: f32,
+    #[serde(default)]
+    pub injured: bool,
+    /// Propensity (Roth-Erev) or value (Q-learning) of cooperating
+    #[serde(default)]
+    pub q_c: f32,
+    /// Propensity (Roth-Erev) or value (Q-learning) of competing
+    #[serde(default)]
+    pub q_d: f32,
+    /// Probability of cooperating when strategies are continuous
+    #[serde(default = "default_level")]
+    pub coop_level: f32,
+    #[serde(default)]
+    pub behaviour: Behaviour,
+    /// Own aspiration level, only used by habituating aspirations
+    #[serde(default)]
+    pub aspiration: f32,
+    /// Group the evacuee evacuates with, alone if missing
+    #[serde(default)]
+    pub group: Option<usize>,
+    /// Payoff of the last contest the evacuee took part in
+    #[serde(default)]
+    pub payoff: f32,
+}
+
+impl Default for EvacueeCell {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            strategy: Strategy::Cooperative,
+            x: 0,
+            y: 0,
+            pr_c: 0.,
+            pr_d: 0.,
+            class: 0,
+            speed: default_speed(),
+            move_budget: 0.,
+            strength: default_strength(),
+            injured: false,
+            q_c: 0.,
+            q_d: 0.,
+            coop_level: default_level(),
+            behaviour: Behaviour::Adaptive,
+            aspiration: 0.,
+            group: None,
+            payoff: 0.,
+        }
+    }
+}
+
+impl EvacueeCell {
+    /// Add the movement of a new tick to the budget
+    #[inline]
+    pub fn accumulate_budget(&mut self) {
+        self.move_budget += self.speed;
+    }
+
+    /// Whether the evacuee has enough budget left to take a sub-step
+    #[inline]
+    pub fn can_move(&self) -> bool {
+        self.move_budget >= 1.
+    }
+
+    /// Spend a sub-step. The step is spent even if the evacuee ends up not moving,
+    /// so blocked evacuees do not bank movement for later ticks
+    #[inline]
+    pub fn spend_substep(&mut self) {
+        self.move_budget -= 1.;
+    }
+
+    /// Lose `ticks` worth of movement
+    #[inline]
+    pub fn penalise(&mut self, ticks: f32) {
+        self.move_budget -= ticks * self.speed;
+    }
+
+    /// Injure the evacuee, slowing it down. Repeated injuries have no further effect
+    pub fn injure(&mut self) {
+        if !self.injured {
+            self.injured = true;
+            self.speed *= INJURED_SPEED_FACTOR;
+        }
+    }
 }
 
 impl PartialEq for EvacueeCell {
@@ -17,3 +129,64 @@ impl PartialEq for EvacueeCell {
         self.x == other.x && self.y == other.y
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn substeps_per_tick(speed: f32, ticks: usize) -> Vec<usize> {
+        let mut evac = EvacueeCell {
+            speed,
+            ..Default::default()
+        };
+        (0..ticks)
+            .map(|_| {
+                evac.accumulate_budget();
+                let mut n = 0;
+                while evac.can_move() {
+                    evac.spend_substep();
+                    n += 1;
+                }
+                n
+            })
+            .collect()
+    }
+
+    #[test]
+    fn normal_speed_moves_every_tick() {
+        assert_eq!(substeps_per_tick(1., 4), vec![1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn fast_evacuee_takes_substeps() {
+        assert_eq!(substeps_per_tick(2., 3), vec![2, 2, 2]);
+        assert_eq!(substeps_per_tick(1.5, 4), vec![1, 2, 1, 2]);
+    }
+
+    #[test]
+    fn penalty_skips_ticks() {
+        let mut evac = EvacueeCell::default();
+        evac.penalise(2.);
+        evac.accumulate_budget();
+        assert!(!evac.can_move());
+        evac.accumulate_budget();
+        assert!(!evac.can_move());
+        evac.accumulate_budget();
+        assert!(evac.can_move());
+    }
+
+    #[test]
+    fn injury_is_applied_once() {
+        let mut evac = EvacueeCell::default();
+        evac.injure();
+        evac.injure();
+        assert!(evac.injured);
+        assert_eq!(evac.speed, INJURED_SPEED_FACTOR);
+    }
+
+    #[test]
+    fn slow_evacuee_skips_ticks() {
+        assert_eq!(substeps_per_tick(0.5, 4), vec![0, 1, 0, 1]);
+        assert_eq!(substeps_per_tick(0., 3), vec![0, 0, 0]);
+    }
+}
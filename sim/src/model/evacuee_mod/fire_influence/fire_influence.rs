@@ -1,7 +1,12 @@
 use crate::model::{
     evacuee_mod::{
-        strategies::aspiration_strategy::{AspirationStrategy, LogAspManip},
-        strategy::{strategy_rewards, RSTP},
+        game::GameType,
+        strategies::{
+            aspiration_strategy::{AspirationStrategy, LogAspManip},
+            ratio_strategy::RatioStrategy,
+            reward_strategy::RewardStrategy,
+        },
+        strategy::RSTP,
     },
     lerp::equations::LerpStruct,
     // file_handling::file_handler::FileHandler,
@@ -21,19 +26,24 @@ use super::{
 pub struct FireInfluence {
     pub fire_state: Box<dyn FrontierStructure + Send>,
     pub fire_area: usize,
+    /// Current step of the simulation
+    pub step: u64,
     /// Dynamic measurement
     pub movement: Box<dyn DynamicInfluence + Send>,
     /// Aspiration function used
     pub aspiration: Box<dyn AspirationStrategy + Send>,
     /// Ratio function used
-    pub ratio: LerpStruct,
+    pub ratio: Box<dyn RatioStrategy + Send>,
     /// Reward game function used
-    pub reward_game: LerpStruct,
+    pub reward_game: Box<dyn RewardStrategy + Send>,
+    /// Payoff structure of the conflict game
+    pub game: GameType,
 }
 
 impl FireInfluence {
     pub fn reset(&mut self) {
         self.fire_area = 0;
+        self.step = 0;
         self.fire_state.reset();
     }
 }
@@ -45,25 +55,27 @@ impl Default for FireInfluence {
             .sqrt();
         Self {
             fire_area: 0,
+            step: 0,
             movement: Box::new(ClosestDistance::default()),
             aspiration: Box::new(LogAspManip::default()),
-            ratio: LerpStruct::new(
+            ratio: Box::new(LerpStruct::new(
                 0.,
                 mx_dist,
                 0.,
                 MAX_REWARD,
                 1.,
                 crate::model::lerp::equations::Equation::Linear,
-            ),
-            reward_game: LerpStruct::new(
+            )),
+            reward_game: Box::new(LerpStruct::new(
                 0.,
                 mx_dist,
                 MAX_REWARD,
                 0.,
                 1.,
                 crate::model::lerp::equations::Equation::Linear,
-            ),
+            )),
             fire_state: Box::new(Frontier::default()),
+            game: GameType::default(),
         }
     }
 }
@@ -101,28 +113,40 @@ impl FireInfluence {
                 csv : true
             );
         }
-        strategy_rewards(n, r_t, reward_b)
+        self.game.payoffs(n, r_t, reward_b)
     }
 
     #[inline]
     pub fn calculate_aspiration(&self) -> f32 {
-        self.aspiration.calculate_asp(self.fire_area)
+        self.aspiration.calculate_asp(self.fire_area, self.step)
+    }
+
+    /// Aspiration of an evacuee with own aspiration level `own`
+    #[inline]
+    pub fn calculate_agent_aspiration(&self, own: f32) -> f32 {
+        self.aspiration.agent_asp(self.fire_area, self.step, own)
     }
 
     #[inline]
     pub fn calculate_ratio(&self, dist: f32) -> f32 {
-        self.ratio.eval(dist)
+        self.ratio.calculate_ratio(dist)
     }
 
     #[inline]
     pub fn calculate_reward(&self, dist: f32) -> f32 {
-        self.reward_game.eval(dist)
+        self.reward_game.calculate_reward(dist)
     }
 
     pub fn on_step(&mut self, loc: &Loc) {
         self.fire_area += 1;
         self.fire_state.on_fire_update(loc);
     }
+
+    /// The fire at `loc` was suppressed
+    pub fn on_suppressed(&mut self, loc: &Loc) {
+        self.fire_area = self.fire_area.saturating_sub(1);
+        self.fire_state.on_fire_removed(loc);
+    }
 }
 
 #[cfg(test)]
@@ -169,7 +193,7 @@ mod tests {
 
             let ratio = LerpStruct::new(0., 100., 0., MAX_REWARD, 1., Equation::EaseIn);
             let fire_infl = FireInfluence {
-                ratio: ratio.clone(),
+                ratio: Box::new(ratio.clone()),
                 fire_state: Box::new(frontier),
                 ..Default::default()
             };
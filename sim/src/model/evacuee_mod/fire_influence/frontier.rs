@@ -11,6 +11,9 @@ use mockall::*;
 pub trait FrontierStructure {
     fn on_fire_update(&mut self, loc: &Loc);
 
+    /// The fire at `loc` was put out
+    fn on_fire_removed(&mut self, loc: &Loc);
+
     fn closest_point(&self, loc: &Loc) -> Option<f32>;
 
     fn reset(&mut self);
@@ -41,6 +44,11 @@ impl Frontier {
         self.trees.get_mut(x).map(|tree| tree.insert(*y))
     }
 
+    fn remove(&mut self, Loc(x, y): &Loc) -> Option<bool> {
+        let x = *x as usize;
+        self.trees.get_mut(x).map(|tree| tree.remove(y))
+    }
+
     fn nearest(&self, loc: &Loc) -> Option<f32> {
         let calc = |lc: &(i32, i32)| {
             // d^2, Reason of why not + 1. is because, a having a distance of 0 will be filtered from the neighbours
@@ -81,6 +89,10 @@ impl FrontierStructure for Frontier {
         self.update(loc);
     }
 
+    fn on_fire_removed(&mut self, loc: &Loc) {
+        self.remove(loc);
+    }
+
     fn closest_point(&self, loc: &Loc) -> Option<f32> {
         self.nearest(loc)
     }
@@ -154,6 +166,16 @@ mod frontier_tests {
         assert_relative_eq!(front.closest_point(&Loc(4, 1)).unwrap(), (2.0_f32));
     }
 
+    #[test]
+    fn frontier_removed_point() {
+        let mut front = Frontier::new(5);
+        front.update_vec(&vec![Loc(0, 0), Loc(3, 0)]);
+        front.on_fire_removed(&Loc(3, 0));
+        assert_relative_eq!(front.closest_point(&Loc(3, 0)).unwrap(), 9.);
+        front.on_fire_removed(&Loc(0, 0));
+        assert_eq!(front.closest_point(&Loc(3, 0)), None);
+    }
+
     #[test]
     fn frontier_column_line() {
         let mut front = Frontier::new(5);
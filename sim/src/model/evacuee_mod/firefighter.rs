@@ -0,0 +1,165 @@
+use serde::Deserialize;
+
+use crate::model::misc::misc_func::Loc;
+
+fn default_rate() -> usize {
+    1
+}
+
+/// Firefighters entering the building through the exits
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct FirefighterPolicy {
+    /// Step at which the firefighters reach the building
+    pub arrival: u64,
+    /// Number of firefighters sent in
+    pub count: usize,
+    /// Fire cells around it a firefighter puts out every tick
+    #[serde(default = "default_rate")]
+    pub rate: usize,
+}
+
+/// Firefighters sent in so far and the fire cells they put out
+#[derive(Debug, Clone)]
+pub struct Firefighters {
+    pub policy: FirefighterPolicy,
+    dispatched: usize,
+    /// Id of the next firefighter, after the ids of the evacuees
+    next_id: usize,
+    /// Fire cells put out during the last evacuee step, cleared from the fire grid on the next fire step
+    pending: Vec<Loc>,
+    /// Fire cells put out since the start
+    suppressed: usize,
+}
+
+impl Firefighters {
+    pub fn new(policy: FirefighterPolicy) -> Self {
+        Self {
+            policy,
+            dispatched: 0,
+            next_id: 0,
+            pending: vec![],
+            suppressed: 0,
+        }
+    }
+
+    /// Start over, firefighters get ids from `first_id` on
+    pub fn reset(&mut self, first_id: usize) {
+        self.dispatched = 0;
+        self.next_id = first_id;
+        self.pending.clear();
+        self.suppressed = 0;
+    }
+
+    /// Ids of the firefighters entering at `step` given the number of `free` cells next to the exits
+    pub fn dispatch(&mut self, step: u64, free: usize) -> Vec<usize> {
+        if step < self.policy.arrival {
+            return vec![];
+        }
+        let n = (self.policy.count - self.dispatched).min(free);
+        self.dispatched += n;
+        self.next_id += n;
+        (self.next_id - n..self.next_id).collect()
+    }
+
+    /// Number of firefighters sent in so far
+    pub fn dispatched(&self) -> usize {
+        self.dispatched
+    }
+
+    /// Number of fire cells put out so far
+    pub fn suppressed(&self) -> usize {
+        self.suppressed
+    }
+
+    /// Put out up to the rate of the policy out of the `burning` cells around a firefighter
+    pub fn suppress(&mut self, burning: impl Iterator<Item = Loc>) -> usize {
+        let before = self.pending.len();
+        for loc in burning {
+            if self.pending.len() - before == self.policy.rate {
+                break;
+            }
+            if !self.pending.contains(&loc) {
+                self.pending.push(loc);
+            }
+        }
+        let put_out = self.pending.len() - before;
+        self.suppressed += put_out;
+        put_out
+    }
+
+    /// Fire cells put out since the last call
+    pub fn take_suppressed(&mut self) -> Vec<Loc> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Weight of stepping into every cell of `neigh`, the closer to the fire the higher.
+    /// `fire_distsq` - squared distance to the closest fire cell, cells are equally likely without fire
+    pub fn move_weights(
+        neigh: &[(Loc, f32)],
+        fire_distsq: impl Fn(&Loc) -> Option<f32>,
+    ) -> Vec<f32> {
+        let dists = neigh
+            .iter()
+            .map(|(l, _)| fire_distsq(l).map_or(0., f32::sqrt))
+            .collect::<Vec<_>>();
+        let closest = dists.iter().copied().fold(f32::INFINITY, f32::min);
+        let all = neigh
+            .iter()
+            .zip(dists)
+            .map(|((_, cost), d)| (closest - d).exp() / cost)
+            .collect::<Vec<_>>();
+        let s: f32 = all.iter().sum();
+        all.into_iter().map(|w| w / s).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn firefighters() -> Firefighters {
+        let mut f = Firefighters::new(FirefighterPolicy {
+            arrival: 10,
+            count: 3,
+            rate: 2,
+        });
+        f.reset(100);
+        f
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let mut f = firefighters();
+        assert!(f.dispatch(5, 4).is_empty());
+        assert_eq!(f.dispatch(10, 2), vec![100, 101]);
+        assert_eq!(f.dispatch(11, 2), vec![102]);
+        assert!(f.dispatch(12, 2).is_empty());
+        assert_eq!(f.dispatched(), 3);
+    }
+
+    #[test]
+    fn test_suppress() {
+        let mut f = firefighters();
+        let burning = [Loc(1, 1), Loc(1, 2), Loc(1, 3)];
+        assert_eq!(f.suppress(burning.into_iter()), 2);
+        // Cells already put out are not counted twice
+        assert_eq!(f.suppress(burning[1..].iter().copied()), 1);
+        assert_eq!(f.take_suppressed().len(), 3);
+        assert!(f.take_suppressed().is_empty());
+        assert_eq!(f.suppress(burning.into_iter()), 2);
+        assert_eq!(f.suppressed(), 5);
+    }
+
+    #[test]
+    fn test_move_towards_fire() {
+        let fire = Loc(0, 0);
+        let neigh = [(Loc(1, 0), 1.), (Loc(3, 0), 1.)];
+        let weights = Firefighters::move_weights(&neigh, |l| {
+            Some(((l.0 - fire.0).pow(2) + (l.1 - fire.1).pow(2)) as f32)
+        });
+        assert!(weights[0] > weights[1]);
+        assert!((weights.iter().sum::<f32>() - 1.).abs() < 1e-5);
+        let weights = Firefighters::move_weights(&neigh, |_| None);
+        assert_eq!(weights, vec![0.5, 0.5]);
+    }
+}
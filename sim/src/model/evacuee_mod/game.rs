@@ -0,0 +1,429 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::strategy::{strategy_rewards, Strategy, RSTP};
+use crate::model::misc::misc_func::Loc;
+
+/// How a conflict between two or more competitive evacuees is resolved
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum ArgumentResolution {
+    /// Nobody gets the cell
+    #[default]
+    Block,
+    /// A random competitive evacuee gets the cell
+    RandomWinner,
+    /// A competitive evacuee drawn with probability proportional to its strength gets the cell
+    WeightedWinner,
+    /// As `WeightedWinner`, the losers additionally lose `penalty` ticks of movement
+    PenalisedWinner { penalty: f32 },
+}
+
+impl ArgumentResolution {
+    /// Whether anyone gets the cell
+    #[inline]
+    pub fn has_winner(&self) -> bool {
+        *self != ArgumentResolution::Block
+    }
+
+    /// Whether the winner is drawn according to strength
+    #[inline]
+    pub fn strength_weighted(&self) -> bool {
+        matches!(
+            self,
+            ArgumentResolution::WeightedWinner | ArgumentResolution::PenalisedWinner { .. }
+        )
+    }
+
+    /// Ticks of movement lost by the losers
+    #[inline]
+    pub fn penalty(&self) -> f32 {
+        match self {
+            ArgumentResolution::PenalisedWinner { penalty } => *penalty,
+            _ => 0.,
+        }
+    }
+}
+
+/// Resolution of arguments overriding the one of the game
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub struct ArgumentPolicy {
+    pub resolution: ArgumentResolution,
+    /// Probability of every evacuee involved in an argument to get injured
+    #[serde(default)]
+    pub injury_risk: f32,
+}
+
+/// Pushing of cooperative neighbours by competitive evacuees
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct PushPolicy {
+    /// Probability of a competitive evacuee pushing a cooperative neighbour closer to the exit
+    pub probability: f32,
+    /// Probability of the pushed evacuee getting injured
+    #[serde(default)]
+    pub injury_risk: f32,
+    /// Stimulus received by the pushed evacuee, in [-1, 0]
+    #[serde(default = "default_push_stimulus")]
+    pub stimulus: f32,
+}
+
+pub fn default_push_stimulus() -> f32 {
+    -1.
+}
+
+impl PushPolicy {
+    /// Check that the stimulus of the pushed evacuee lies in [-1, 0]
+    pub fn validate(&self) -> Result<(), String> {
+        let stimulus = self.stimulus;
+        if (-1. ..=0.).contains(&stimulus) {
+            Ok(())
+        } else {
+            Err(format!("stimulus must lie in [-1, 0], got {stimulus}"))
+        }
+    }
+}
+
+/// Cell and index of the evacuee a pusher at `origin` shoves aside, the closest to the exit
+/// out of the `neighbours` closer to it than the pusher.
+/// `pushable` - index of the evacuee on a cell if it can be pushed, `dist` - distance of a cell to the exit
+pub fn push_target(
+    origin: Loc,
+    neighbours: impl IntoIterator<Item = Loc>,
+    pushable: impl Fn(&Loc) -> Option<usize>,
+    dist: impl Fn(&Loc) -> f32,
+) -> Option<(Loc, usize)> {
+    let own = dist(&origin);
+    neighbours
+        .into_iter()
+        .filter_map(|loc| Some((loc, pushable(&loc)?, dist(&loc))))
+        .filter(|(_, _, d)| *d < own)
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(loc, j, _)| (loc, j))
+}
+
+/// Cell a pushed evacuee is shoved into, one of the `free` cells around it
+/// or the cell of the pusher at `origin` if there is none, swapping places with it
+pub fn push_destination(origin: Loc, free: &[Loc], rng: &mut impl Rng) -> Loc {
+    free.choose(rng).copied().unwrap_or(origin)
+}
+
+/// Payoff structure of the game played over a contested cell.
+/// `b` is the reward of the cell, `n` the number of competing evacuees and `r_t` the fire ratio
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum GameType {
+    /// Prisoner's dilemma, mutual defection blocks the cell
+    #[default]
+    PrisonersDilemma,
+    /// Hawk-dove (snowdrift), hawks fight over the cell at `cost` and one of them gets through
+    HawkDove { cost: f32 },
+    /// Stag hunt, cooperating pays the most but defecting pays `temptation * b / n` regardless.
+    /// Hunting hares together catches no stag, mutual defection blocks the cell
+    StagHunt { temptation: f32 },
+    /// Parametric matrix, the entries are multiples of the share `b / n`
+    Custom {
+        r: f32,
+        s: f32,
+        t: f32,
+        p: f32,
+        #[serde(default)]
+        argument: ArgumentResolution,
+    },
+    /// N-player public goods game among every evacuee within `radius` of the contested cell.
+    /// Cooperators pay `cost` times the reward into a pool multiplied by `multiplier` and split
+    /// among the group. Defecting pays more as long as `multiplier` is smaller than the group.
+    /// Competing contestants get through in order when the pool pays the cooperators back.
+    /// Bystanders only contribute their strategy, the contestants alone are paid and learn
+    PublicGoods {
+        multiplier: f32,
+        cost: f32,
+        radius: f32,
+    },
+    /// Volunteer's dilemma among every evacuee within `radius` of the contested cell.
+    /// Passage is orderly if anyone volunteers, volunteers pay `cost` times the reward.
+    /// Bystanders only contribute their strategy, the contestants alone are paid and learn
+    VolunteersDilemma { cost: f32, radius: f32 },
+}
+
+impl GameType {
+    /// RSTP payoffs of the game
+    pub fn payoffs(&self, n: usize, r_t: f32, b: f32) -> RSTP {
+        let share = b / n as f32;
+        match *self {
+            GameType::PrisonersDilemma => strategy_rewards(n, r_t, b),
+            GameType::HawkDove { cost } => (share, 0., b, (b * (1. - r_t) - cost) / n as f32),
+            GameType::StagHunt { temptation } => {
+                (share, -share * r_t, temptation * share, temptation * share)
+            }
+            GameType::Custom { r, s, t, p, .. } => (r * share, s * share, t * share, p * share),
+            GameType::PublicGoods {
+                multiplier, cost, ..
+            } => (
+                b * cost * (multiplier - 1.),
+                b * cost * (multiplier / n as f32 - 1.),
+                b * cost * multiplier * (n - 1) as f32 / n as f32,
+                0.,
+            ),
+            GameType::VolunteersDilemma { cost, .. } => {
+                (b * (1. - cost), b * (1. - cost), b, -b * r_t / n as f32)
+            }
+        }
+    }
+
+    /// Radius of the group playing the game, `None` for games among the contestants only
+    pub fn group_radius(&self) -> Option<f32> {
+        match self {
+            GameType::PublicGoods { radius, .. } | GameType::VolunteersDilemma { radius, .. } => {
+                Some(*radius)
+            }
+            _ => None,
+        }
+    }
+
+    /// Payoff of an N-player game for an evacuee playing `strategy` in a group of `group`
+    /// evacuees of which `others` other evacuees cooperate. `rstp` are the payoffs of the group size
+    pub fn group_payoff(&self, rstp: RSTP, strategy: Strategy, others: usize, group: usize) -> f32 {
+        let (r, s, t, p) = rstp;
+        match (self, strategy) {
+            (GameType::VolunteersDilemma { .. }, Strategy::Cooperative) => r,
+            (GameType::VolunteersDilemma { .. }, Strategy::Competitive) if others > 0 => t,
+            (GameType::VolunteersDilemma { .. }, Strategy::Competitive) => p,
+            // Public goods payoffs are linear in the number of cooperators
+            (_, strategy) => {
+                let frac = if group > 1 {
+                    others as f32 / (group - 1) as f32
+                } else {
+                    0.
+                };
+                match strategy {
+                    Strategy::Cooperative => s + (r - s) * frac,
+                    Strategy::Competitive => p + (t - p) * frac,
+                }
+            }
+        }
+    }
+
+    /// Resolution rule of the game when more than one evacuee competes
+    pub fn argument_resolution(&self) -> ArgumentResolution {
+        match self {
+            GameType::PrisonersDilemma => ArgumentResolution::Block,
+            GameType::HawkDove { .. } => ArgumentResolution::RandomWinner,
+            GameType::StagHunt { .. } => ArgumentResolution::Block,
+            GameType::Custom { argument, .. } => *argument,
+            GameType::PublicGoods { .. } | GameType::VolunteersDilemma { .. } => {
+                ArgumentResolution::Block
+            }
+        }
+    }
+
+    /// Resolution rule of an N-player game given the `cooperators` among the `group` around the cell.
+    /// Passage is orderly, a random contestant getting the cell, if anyone volunteers or
+    /// if the public goods pool pays the cooperators back. Other games use `argument_resolution`
+    pub fn group_resolution(&self, cooperators: usize, group: usize) -> ArgumentResolution {
+        let orderly = match *self {
+            GameType::VolunteersDilemma { .. } => cooperators > 0,
+            GameType::PublicGoods { multiplier, .. } => {
+                multiplier * cooperators as f32 >= group as f32
+            }
+            _ => return self.argument_resolution(),
+        };
+        if orderly {
+            ArgumentResolution::RandomWinner
+        } else {
+            ArgumentResolution::Block
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::misc::misc_func::relative_eq_close;
+    use approx::assert_relative_eq;
+    use proptest::prelude::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    proptest! {
+        #[test]
+        fn test_prisoners_dilemma_unchanged(n in 1..=4usize, r_t in 0f32..20., b in 0.1f32..20.) {
+            prop_assert_eq!(GameType::PrisonersDilemma.payoffs(n, r_t, b), strategy_rewards(n, r_t, b));
+        }
+
+        #[test]
+        fn test_hawk_dove_order(n in 2..=4usize, r_t in 0f32..1., b in 0.1f32..20., extra in 0.1f32..10.) {
+            // fights cost more than the cell is worth, T > R > S > P
+            let (r, s, t, p) = GameType::HawkDove { cost: b + extra }.payoffs(n, r_t, b);
+            prop_assert!(t > r && r > s && s > p);
+        }
+
+        #[test]
+        fn test_stag_hunt_order(n in 1..=4usize, r_t in 0.01f32..20., b in 0.1f32..20., temptation in 0f32..0.99) {
+            // R > T >= P > S
+            let (r, s, t, p) = GameType::StagHunt { temptation }.payoffs(n, r_t, b);
+            prop_assert!(r > t && t >= p && p > s);
+        }
+
+        #[test]
+        fn test_custom_scaled(n in 1..=4usize, b in 0.1f32..20., c in (-5f32..5., -5f32..5., -5f32..5., -5f32..5.)) {
+            let game = GameType::Custom { r: c.0, s: c.1, t: c.2, p: c.3, argument: ArgumentResolution::Block };
+            let (r, s, t, p) = game.payoffs(n, 1., b);
+            let share = b / n as f32;
+            prop_assert!(relative_eq_close(r, c.0 * share));
+            prop_assert!(relative_eq_close(s, c.1 * share));
+            prop_assert!(relative_eq_close(t, c.2 * share));
+            prop_assert!(relative_eq_close(p, c.3 * share));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_public_goods_dilemma(n in 2..=20usize, r_t in 0f32..1., b in 0.1f32..20., cost in 0.1f32..1., m in 1.01f32..2.) {
+            // defecting pays more than cooperating while full cooperation beats everyone defecting
+            let game = GameType::PublicGoods { multiplier: m, cost, radius: 2. };
+            let rstp = game.payoffs(n, r_t, b);
+            for others in 0..n {
+                let coop = game.group_payoff(rstp, Strategy::Cooperative, others, n);
+                let def = game.group_payoff(rstp, Strategy::Competitive, others, n);
+                prop_assert!(def > coop);
+            }
+            prop_assert!(rstp.0 > rstp.3);
+        }
+    }
+
+    #[test]
+    fn test_public_goods_matches_pool() {
+        let (m, cost, b, n) = (1.5, 0.5, 10., 4);
+        let game = GameType::PublicGoods {
+            multiplier: m,
+            cost,
+            radius: 2.,
+        };
+        let rstp = game.payoffs(n, 0.5, b);
+        // 3 cooperators out of 4
+        let pool = m * cost * b * 3. / n as f32;
+        assert_relative_eq!(
+            game.group_payoff(rstp, Strategy::Cooperative, 2, n),
+            pool - cost * b
+        );
+        assert_relative_eq!(game.group_payoff(rstp, Strategy::Competitive, 3, n), pool);
+        assert_relative_eq!(game.group_payoff(rstp, Strategy::Competitive, 0, n), 0.);
+    }
+
+    #[test]
+    fn test_volunteers_dilemma() {
+        let game = GameType::VolunteersDilemma {
+            cost: 0.25,
+            radius: 3.,
+        };
+        let rstp = game.payoffs(3, 0.5, 8.);
+        assert_eq!(game.group_radius(), Some(3.));
+        assert_eq!(game.group_payoff(rstp, Strategy::Cooperative, 0, 3), 6.);
+        assert_eq!(game.group_payoff(rstp, Strategy::Competitive, 1, 3), 8.);
+        assert!(game.group_payoff(rstp, Strategy::Competitive, 0, 3) < 0.);
+        assert_eq!(GameType::PrisonersDilemma.group_radius(), None);
+    }
+
+    #[test]
+    fn test_argument_resolution() {
+        assert_eq!(
+            GameType::PrisonersDilemma.argument_resolution(),
+            ArgumentResolution::Block
+        );
+        assert_eq!(
+            GameType::HawkDove { cost: 1. }.argument_resolution(),
+            ArgumentResolution::RandomWinner
+        );
+        assert_eq!(
+            GameType::StagHunt { temptation: 0.5 }.argument_resolution(),
+            ArgumentResolution::Block
+        );
+        let custom = GameType::Custom {
+            r: 1.,
+            s: 0.,
+            t: 2.,
+            p: -1.,
+            argument: ArgumentResolution::RandomWinner,
+        };
+        assert_eq!(
+            custom.argument_resolution(),
+            ArgumentResolution::RandomWinner
+        );
+    }
+
+    #[test]
+    fn test_group_resolution() {
+        let volunteers = GameType::VolunteersDilemma {
+            cost: 0.25,
+            radius: 3.,
+        };
+        assert_eq!(
+            volunteers.group_resolution(1, 5),
+            ArgumentResolution::RandomWinner
+        );
+        assert_eq!(volunteers.group_resolution(0, 5), ArgumentResolution::Block);
+        let public = GameType::PublicGoods {
+            multiplier: 2.,
+            cost: 0.5,
+            radius: 3.,
+        };
+        assert_eq!(
+            public.group_resolution(2, 4),
+            ArgumentResolution::RandomWinner
+        );
+        assert_eq!(public.group_resolution(1, 4), ArgumentResolution::Block);
+        assert_eq!(
+            GameType::HawkDove { cost: 1. }.group_resolution(0, 4),
+            ArgumentResolution::RandomWinner
+        );
+    }
+
+    #[test]
+    fn test_push_policy_defaults() {
+        let policy: PushPolicy = serde_json::from_str(r#"{"probability": 0.3}"#).unwrap();
+        assert_eq!(policy.probability, 0.3);
+        assert_eq!(policy.injury_risk, 0.);
+        assert_eq!(policy.stimulus, -1.);
+        assert!(policy.validate().is_ok());
+        for stimulus in [0.5, -1.5, f32::NAN] {
+            assert!(PushPolicy { stimulus, ..policy }.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_push_target() {
+        let origin = Loc(2, 2);
+        let dist = |l: &Loc| l.0 as f32;
+        let neighbours = [Loc(1, 1), Loc(1, 2), Loc(2, 1), Loc(3, 2)];
+        let on = |cells: &[(Loc, usize)], l: &Loc| cells.iter().find(|(c, _)| c == l).map(|c| c.1);
+        // The pushable neighbour closest to the exit
+        let ahead = [(Loc(1, 2), 7), (Loc(2, 1), 8)];
+        let target = push_target(origin, neighbours, |l| on(&ahead, l), dist);
+        assert_eq!(target, Some((Loc(1, 2), 7)));
+        // Nobody closer to the exit than the pusher
+        let behind = [(Loc(2, 1), 8), (Loc(3, 2), 9)];
+        let target = push_target(origin, neighbours, |l| on(&behind, l), dist);
+        assert_eq!(target, None);
+        assert_eq!(push_target(origin, neighbours, |_| None, dist), None);
+    }
+
+    #[test]
+    fn test_push_destination() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let origin = Loc(2, 2);
+        // Shoved aside into a free cell
+        let free = [Loc(0, 1), Loc(0, 2)];
+        assert!(free.contains(&push_destination(origin, &free, &mut rng)));
+        // Swapped with the pusher
+        assert_eq!(push_destination(origin, &[], &mut rng), origin);
+    }
+
+    #[test]
+    fn test_resolution_properties() {
+        let penalised = ArgumentResolution::PenalisedWinner { penalty: 2. };
+        assert!(!ArgumentResolution::Block.has_winner());
+        assert!(penalised.has_winner() && penalised.strength_weighted());
+        assert!(!ArgumentResolution::RandomWinner.strength_weighted());
+        assert_eq!(penalised.penalty(), 2.);
+        assert_eq!(ArgumentResolution::WeightedWinner.penalty(), 0.);
+    }
+}
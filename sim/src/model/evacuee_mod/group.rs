@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+
+use krabmaga::HashMap;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use crate::model::misc::misc_func::Loc;
+
+use super::strategy::Strategy;
+
+/// Share of the groups with a given number of members
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct GroupSize {
+    pub size: usize,
+    /// Relative share of the groups, does not need to sum up to one
+    pub proportion: f32,
+}
+
+/// Families or colleagues evacuating together
+#[derive(Debug, Clone, Deserialize)]
+pub struct GroupPolicy {
+    pub sizes: Vec<GroupSize>,
+    /// Largest distance between members before the ones ahead wait for the stragglers
+    pub max_distance: f32,
+}
+
+impl GroupPolicy {
+    /// Sizes of the groups `evac_num` evacuees are split into, the last group is cut short if needed
+    pub fn split(&self, evac_num: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        if self.sizes.is_empty() {
+            return vec![1; evac_num];
+        }
+        let dist = WeightedIndex::new(self.sizes.iter().map(|s| s.proportion))
+            .expect("Group size proportions are not valid");
+        let mut left = evac_num;
+        let mut sizes = vec![];
+        while left > 0 {
+            let size = self.sizes[dist.sample(rng)].size.clamp(1, left);
+            sizes.push(size);
+            left -= size;
+        }
+        sizes
+    }
+}
+
+/// Position and strategy of a group member at the start of a tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Member {
+    pub id: usize,
+    pub group: usize,
+    pub loc: Loc,
+    /// Static influence of the cell of the member, lower is closer to the exit
+    pub exit_dist: f32,
+    pub strategy: Strategy,
+}
+
+/// Groups of evacuees sharing their strategy and waiting for each other
+#[derive(Debug, Clone)]
+pub struct Groups {
+    pub policy: GroupPolicy,
+    /// Number of members of every group at the start of the simulation
+    sizes: HashMap<usize, usize>,
+    /// Strategy played by all the members of a group
+    shared: HashMap<usize, Strategy>,
+    /// Members waiting for a straggler
+    waiting: HashSet<usize>,
+}
+
+impl Groups {
+    pub fn new(policy: GroupPolicy) -> Self {
+        Self {
+            policy,
+            sizes: HashMap::new(),
+            shared: HashMap::new(),
+            waiting: HashSet::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.sizes.clear();
+        self.shared.clear();
+        self.waiting.clear();
+    }
+
+    /// Count a new member of `group`
+    pub fn add_member(&mut self, group: usize) {
+        *self.sizes.entry(group).or_default() += 1;
+    }
+
+    /// Number of members of `group` at the start of the simulation
+    pub fn size(&self, group: usize) -> usize {
+        self.sizes.get(&group).copied().unwrap_or(1)
+    }
+
+    /// Sizes of the groups and the number of evacuees in groups of that size, smallest first
+    pub fn population_by_size(&self) -> Vec<(usize, usize)> {
+        let mut population: HashMap<usize, usize> = HashMap::new();
+        for size in self.sizes.values() {
+            *population.entry(*size).or_default() += size;
+        }
+        let mut population = population.into_iter().collect::<Vec<_>>();
+        population.sort();
+        population
+    }
+
+    /// Refresh the shared strategies and the waiting members given every member still in the building.
+    /// A group plays the strategy of its majority, cooperating on ties. A member waits when another one
+    /// further from the exit is more than the maximum distance away
+    pub fn update(&mut self, members: &[Member]) {
+        let mut by_group: HashMap<usize, Vec<&Member>> = HashMap::new();
+        for m in members {
+            by_group.entry(m.group).or_default().push(m);
+        }
+        self.shared.clear();
+        self.waiting.clear();
+        let max_sq = self.policy.max_distance.powi(2);
+        for (group, members) in by_group {
+            let coop = members
+                .iter()
+                .filter(|m| m.strategy == Strategy::Cooperative)
+                .count();
+            let strategy = if 2 * coop >= members.len() {
+                Strategy::Cooperative
+            } else {
+                Strategy::Competitive
+            };
+            self.shared.insert(group, strategy);
+            for m in members.iter() {
+                let behind = members.iter().any(|o| {
+                    let d = (o.loc.0 - m.loc.0).pow(2) + (o.loc.1 - m.loc.1).pow(2);
+                    d as f32 > max_sq && o.exit_dist > m.exit_dist
+                });
+                if behind {
+                    self.waiting.insert(m.id);
+                }
+            }
+        }
+    }
+
+    pub fn shared_strategy(&self, group: usize) -> Option<Strategy> {
+        self.shared.get(&group).copied()
+    }
+
+    pub fn is_waiting(&self, id: usize) -> bool {
+        self.waiting.contains(&id)
+    }
+
+    /// Number of members waiting for a straggler
+    pub fn waiting(&self) -> usize {
+        self.waiting.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaChaRng;
+
+    fn policy() -> GroupPolicy {
+        GroupPolicy {
+            sizes: vec![
+                GroupSize {
+                    size: 2,
+                    proportion: 1.,
+                },
+                GroupSize {
+                    size: 4,
+                    proportion: 1.,
+                },
+            ],
+            max_distance: 2.,
+        }
+    }
+
+    fn member(id: usize, group: usize, y: i32, strategy: Strategy) -> Member {
+        Member {
+            id,
+            group,
+            loc: Loc(5, y),
+            exit_dist: y as f32,
+            strategy,
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        for n in [0, 1, 7, 50] {
+            let sizes = policy().split(n, &mut rng);
+            assert_eq!(sizes.iter().sum::<usize>(), n);
+            assert!(sizes.iter().all(|s| (1..=4).contains(s)));
+        }
+    }
+
+    #[test]
+    fn test_shared_strategy() {
+        let mut groups = Groups::new(policy());
+        groups.update(&[
+            member(0, 0, 1, Strategy::Competitive),
+            member(1, 0, 2, Strategy::Competitive),
+            member(2, 0, 3, Strategy::Cooperative),
+            member(3, 1, 1, Strategy::Cooperative),
+            member(4, 1, 2, Strategy::Competitive),
+        ]);
+        assert_eq!(groups.shared_strategy(0), Some(Strategy::Competitive));
+        assert_eq!(groups.shared_strategy(1), Some(Strategy::Cooperative));
+        assert_eq!(groups.shared_strategy(2), None);
+    }
+
+    #[test]
+    fn test_wait_for_stragglers() {
+        let mut groups = Groups::new(policy());
+        groups.update(&[
+            member(0, 0, 1, Strategy::Cooperative),
+            member(1, 0, 2, Strategy::Cooperative),
+            member(2, 0, 6, Strategy::Cooperative),
+        ]);
+        assert!(groups.is_waiting(0));
+        assert!(groups.is_waiting(1));
+        assert!(!groups.is_waiting(2));
+        assert_eq!(groups.waiting(), 2);
+    }
+
+    #[test]
+    fn test_population_by_size() {
+        let mut groups = Groups::new(policy());
+        for g in [0, 0, 1, 2, 2, 3, 3] {
+            groups.add_member(g);
+        }
+        assert_eq!(groups.population_by_size(), vec![(1, 1), (2, 6)]);
+        assert_eq!(groups.size(0), 2);
+    }
+}
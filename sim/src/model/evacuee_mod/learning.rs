@@ -0,0 +1,312 @@
+use std::sync::Arc;
+
+use krabmaga::Rng;
+use mockall::automock;
+use rand::RngCore;
+use serde::Deserialize;
+
+use super::{evacuee_cell::EvacueeCell, strategy::Strategy};
+
+/// Shared learning rule, one per agent class
+pub type SharedLearning = Arc<dyn LearningRule + Send + Sync>;
+
+#[automock]
+/// Strategy learning of an evacuee after a contest
+pub trait LearningRule {
+    /// Update the learning state of `evac` given the stimulus `stim` in [-1, 1] it received
+    /// and pick the strategy for its next contest.
+    /// `peers` - last payoff and strategy of the neighbours of `evac`
+    fn update(
+        &self,
+        evac: &mut EvacueeCell,
+        stim: f32,
+        peers: &[(f32, Strategy)],
+        rng: &mut dyn RngCore,
+    );
+}
+
+/// Learning rule of an agent class as declared in the scenario
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum LearningModel {
+    /// Aspiration based reinforcement using the learning rates of the setup
+    #[default]
+    BushMosteller,
+    /// Cumulative reinforcement, `recency` is the rate propensities are forgotten at
+    RothErev { recency: f32 },
+    /// Q-learning with learning rate `alpha` and Boltzmann exploration
+    SoftmaxQ { alpha: f32, temperature: f32 },
+    /// Copy the strategy of the neighbour with the highest last payoff, mutate with probability `exploration`
+    ImitateBest { exploration: f32 },
+}
+
+impl LearningModel {
+    /// Build the rule, `lc` and `ld` are the Bush-Mosteller learning rates
+    pub fn rule(&self, lc: f32, ld: f32) -> SharedLearning {
+        match *self {
+            LearningModel::BushMosteller => Arc::new(BushMosteller { lc, ld }),
+            LearningModel::RothErev { recency } => Arc::new(RothErev { recency }),
+            LearningModel::SoftmaxQ { alpha, temperature } => {
+                Arc::new(SoftmaxQ { alpha, temperature })
+            }
+            LearningModel::ImitateBest { exploration } => Arc::new(ImitateBest { exploration }),
+        }
+    }
+
+    /// Check the parameters of the rule, rates and probabilities must lie in [0, 1]
+    /// and the temperature must be above zero
+    pub fn validate(&self) -> Result<(), String> {
+        let unit = |name: &str, v: f32| {
+            if (0. ..=1.).contains(&v) {
+                Ok(())
+            } else {
+                Err(format!("{name} must lie in [0, 1], got {v}"))
+            }
+        };
+        match *self {
+            LearningModel::BushMosteller => Ok(()),
+            LearningModel::RothErev { recency } => unit("recency", recency),
+            LearningModel::SoftmaxQ { alpha, temperature } => {
+                unit("alpha", alpha)?;
+                if temperature.is_finite() && temperature > 0. {
+                    Ok(())
+                } else {
+                    Err(format!("temperature must be above zero, got {temperature}"))
+                }
+            }
+            LearningModel::ImitateBest { exploration } => unit("exploration", exploration),
+        }
+    }
+}
+
+pub(crate) fn calc_prob(prob: f32, learning: f32, stim: f32) -> f32 {
+    if stim.is_sign_positive() {
+        prob + (1. - prob) * learning * stim
+    } else {
+        prob * (1. + learning * stim)
+    }
+}
+
+/// Draw the strategy of the evacuee given the probability of cooperating
+fn choose(evac: &mut EvacueeCell, pr_coop: f32, rng: &mut dyn RngCore) {
+    let pr_coop = pr_coop.clamp(0., 1.);
+    evac.pr_c = pr_coop;
+    evac.pr_d = 1. - pr_coop;
+    evac.strategy = if rng.gen_bool(pr_coop as f64) {
+        Strategy::Cooperative
+    } else {
+        Strategy::Competitive
+    };
+}
+
+/// Bush-Mosteller reinforcement, the probability of keeping the current strategy
+/// moves towards 1 on a positive stimulus and towards 0 on a negative one
+#[derive(Debug, Clone)]
+pub struct BushMosteller {
+    pub lc: f32,
+    pub ld: f32,
+}
+
+impl LearningRule for BushMosteller {
+    fn update(
+        &self,
+        evac: &mut EvacueeCell,
+        stim: f32,
+        _peers: &[(f32, Strategy)],
+        rng: &mut dyn RngCore,
+    ) {
+        match evac.strategy {
+            Strategy::Competitive => {
+                // ADOPT FOR COOP
+                evac.pr_d = calc_prob(evac.pr_d, self.ld, stim);
+                if !rng.gen_bool(evac.pr_d as f64) {
+                    evac.strategy = Strategy::Cooperative;
+                }
+            }
+            Strategy::Cooperative => {
+                // ADOPT FOR COOP
+                evac.pr_c = calc_prob(evac.pr_c, self.lc, stim);
+                if !rng.gen_bool(evac.pr_c as f64) {
+                    evac.strategy = Strategy::Competitive;
+                }
+            }
+        }
+    }
+}
+
+/// Roth-Erev reinforcement, the propensity of the played strategy accumulates the (shifted) stimulus
+/// and the strategy is picked proportionally to the propensities
+#[derive(Debug, Clone)]
+pub struct RothErev {
+    pub recency: f32,
+}
+
+impl LearningRule for RothErev {
+    fn update(
+        &self,
+        evac: &mut EvacueeCell,
+        stim: f32,
+        _peers: &[(f32, Strategy)],
+        rng: &mut dyn RngCore,
+    ) {
+        let forget = 1. - self.recency;
+        evac.q_c *= forget;
+        evac.q_d *= forget;
+        // Shift the stimulus so reinforcements are never negative
+        let reinforcement = (stim + 1.).max(0.);
+        match evac.strategy {
+            Strategy::Cooperative => evac.q_c += reinforcement,
+            Strategy::Competitive => evac.q_d += reinforcement,
+        }
+        let total = evac.q_c + evac.q_d;
+        let pr_coop = if total > 0. { evac.q_c / total } else { 0.5 };
+        choose(evac, pr_coop, rng);
+    }
+}
+
+/// Q-learning over the two strategies with softmax action selection
+#[derive(Debug, Clone)]
+pub struct SoftmaxQ {
+    pub alpha: f32,
+    pub temperature: f32,
+}
+
+impl LearningRule for SoftmaxQ {
+    fn update(
+        &self,
+        evac: &mut EvacueeCell,
+        stim: f32,
+        _peers: &[(f32, Strategy)],
+        rng: &mut dyn RngCore,
+    ) {
+        let q = match evac.strategy {
+            Strategy::Cooperative => &mut evac.q_c,
+            Strategy::Competitive => &mut evac.q_d,
+        };
+        *q += self.alpha * (stim - *q);
+        let pr_coop = 1. / (1. + ((evac.q_d - evac.q_c) / self.temperature).exp());
+        choose(evac, pr_coop, rng);
+    }
+}
+
+/// Imitation of the most successful neighbour, compared by the payoff of their last contest
+#[derive(Debug, Clone)]
+pub struct ImitateBest {
+    pub exploration: f32,
+}
+
+impl LearningRule for ImitateBest {
+    fn update(
+        &self,
+        evac: &mut EvacueeCell,
+        _stim: f32,
+        peers: &[(f32, Strategy)],
+        rng: &mut dyn RngCore,
+    ) {
+        let adopted = peers
+            .iter()
+            .filter(|(payoff, _)| *payoff > evac.payoff)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or(evac.strategy, |(_, best)| *best);
+        let pr_coop = match adopted {
+            Strategy::Cooperative => 1. - self.exploration,
+            Strategy::Competitive => self.exploration,
+        };
+        choose(evac, pr_coop, rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::misc::misc_func::relative_eq_close;
+    use proptest::prelude::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    fn evac(strategy: Strategy) -> EvacueeCell {
+        EvacueeCell {
+            strategy,
+            pr_c: 0.5,
+            pr_d: 0.5,
+            ..Default::default()
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_roth_erev_probability(stim in -1f32..=1., coop in any::<bool>(), seed in 0..100u64) {
+            let mut rng = ChaChaRng::seed_from_u64(seed);
+            let mut e = evac(if coop { Strategy::Cooperative } else { Strategy::Competitive });
+            e.q_c = 1.;
+            e.q_d = 1.;
+            RothErev { recency: 0.1 }.update(&mut e, stim, &[], &mut rng);
+            prop_assert!(relative_eq_close(e.pr_c, e.q_c / (e.q_c + e.q_d)));
+            prop_assert!(relative_eq_close(e.pr_d, 1. - e.pr_c));
+        }
+
+        #[test]
+        fn test_softmax_q_update(stim in -1f32..=1., alpha in 0f32..=1., seed in 0..100u64) {
+            let mut rng = ChaChaRng::seed_from_u64(seed);
+            let mut e = evac(Strategy::Cooperative);
+            e.q_c = 0.2;
+            SoftmaxQ { alpha, temperature: 1. }.update(&mut e, stim, &[], &mut rng);
+            prop_assert!(relative_eq_close(e.q_c, 0.2 + alpha * (stim - 0.2)));
+            prop_assert_eq!(e.q_d, 0.);
+            prop_assert!(relative_eq_close(e.pr_d, 1. - e.pr_c));
+        }
+    }
+
+    #[test]
+    fn test_bush_mosteller_keeps_strategy_on_full_reward() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let rule = LearningModel::BushMosteller.rule(1., 1.);
+        for strategy in [Strategy::Cooperative, Strategy::Competitive] {
+            let mut e = evac(strategy);
+            rule.update(&mut e, 1., &[], &mut rng);
+            assert_eq!(e.strategy, strategy);
+        }
+    }
+
+    #[test]
+    fn test_roth_erev_rewarded_strategy_gains() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let mut e = evac(Strategy::Cooperative);
+        RothErev { recency: 0. }.update(&mut e, 1., &[], &mut rng);
+        assert_eq!(e.pr_c, 1.);
+        assert_eq!(e.strategy, Strategy::Cooperative);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(LearningModel::BushMosteller.validate().is_ok());
+        assert!(LearningModel::RothErev { recency: 0.1 }.validate().is_ok());
+        assert!(LearningModel::RothErev { recency: 1.5 }.validate().is_err());
+        let softmax = |alpha, temperature| LearningModel::SoftmaxQ { alpha, temperature };
+        assert!(softmax(0.5, 1.).validate().is_ok());
+        assert!(softmax(0.5, 0.).validate().is_err());
+        assert!(softmax(-0.1, 1.).validate().is_err());
+        assert!(softmax(f32::NAN, 1.).validate().is_err());
+    }
+
+    #[test]
+    fn test_imitate_best() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let rule = ImitateBest { exploration: 0. };
+        let peers = [(1., Strategy::Cooperative), (3., Strategy::Competitive)];
+        let mut e = evac(Strategy::Cooperative);
+        rule.update(&mut e, -0.5, &peers, &mut rng);
+        assert_eq!(e.strategy, Strategy::Competitive);
+        assert_eq!((e.pr_c, e.pr_d), (0., 1.));
+        // nobody around did better
+        let mut e = evac(Strategy::Cooperative);
+        e.payoff = 4.;
+        rule.update(&mut e, -0.5, &peers, &mut rng);
+        assert_eq!(e.strategy, Strategy::Cooperative);
+        assert_eq!((e.pr_c, e.pr_d), (1., 0.));
+        // alone, the strategy only mutates
+        let rule = ImitateBest { exploration: 0.25 };
+        let mut e = evac(Strategy::Competitive);
+        rule.update(&mut e, 0.8, &[], &mut rng);
+        assert_eq!((e.pr_c, e.pr_d), (0.25, 0.75));
+    }
+}
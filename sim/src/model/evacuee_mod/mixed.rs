@@ -0,0 +1,98 @@
+use rand::{Rng, RngCore};
+use serde::Deserialize;
+
+use super::strategy::{Strategy, RSTP};
+
+/// Number of bins of the cooperation level histogram
+pub const LEVEL_BINS: usize = 10;
+
+/// How the strategy of an evacuee is represented
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum StrategyRepresentation {
+    /// Every evacuee is either competitive or cooperative
+    #[default]
+    Discrete,
+    /// Every evacuee holds a cooperation level in [0, 1], the probability of cooperating in a contest
+    Continuous,
+}
+
+/// Sample the action played in a contest from the cooperation level
+#[inline]
+pub fn sample_action(level: f32, rng: &mut dyn RngCore) -> Strategy {
+    if rng.gen_bool(level.clamp(0., 1.) as f64) {
+        Strategy::Cooperative
+    } else {
+        Strategy::Competitive
+    }
+}
+
+/// Expected payoff of an evacuee cooperating with probability `own`
+/// against opponents cooperating with average probability `others`
+pub fn expected_payoff((r, s, t, p): RSTP, own: f32, others: f32) -> f32 {
+    own * others * r
+        + own * (1. - others) * s
+        + (1. - own) * others * t
+        + (1. - own) * (1. - others) * p
+}
+
+/// Reinforce the cooperation level towards the played action on a positive stimulus
+/// and away from it on a negative one, `rate` is the learning rate of the played action
+pub fn update_level(level: f32, played: Strategy, rate: f32, stim: f32) -> f32 {
+    let target = match played {
+        Strategy::Cooperative => 1.,
+        Strategy::Competitive => 0.,
+    };
+    (level + rate * stim * (target - level)).clamp(0., 1.)
+}
+
+/// Number of cooperation levels falling in each of `LEVEL_BINS` equal bins of [0, 1]
+pub fn level_histogram(levels: impl Iterator<Item = f32>) -> [usize; LEVEL_BINS] {
+    let mut bins = [0; LEVEL_BINS];
+    for l in levels {
+        let idx = ((l.clamp(0., 1.) * LEVEL_BINS as f32) as usize).min(LEVEL_BINS - 1);
+        bins[idx] += 1;
+    }
+    bins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::misc::misc_func::relative_eq_close;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_pure_levels_match_matrix(rstp in (-5f32..5., -5f32..5., -5f32..5., -5f32..5.)) {
+            prop_assert!(relative_eq_close(expected_payoff(rstp, 1., 1.), rstp.0));
+            prop_assert!(relative_eq_close(expected_payoff(rstp, 1., 0.), rstp.1));
+            prop_assert!(relative_eq_close(expected_payoff(rstp, 0., 1.), rstp.2));
+            prop_assert!(relative_eq_close(expected_payoff(rstp, 0., 0.), rstp.3));
+        }
+
+        #[test]
+        fn test_update_level_bounded(level in 0f32..=1., rate in 0f32..=1., stim in -1f32..=1., coop in any::<bool>()) {
+            let played = if coop { Strategy::Cooperative } else { Strategy::Competitive };
+            let new = update_level(level, played, rate, stim);
+            prop_assert!((0. ..=1.).contains(&new));
+            // a positive stimulus never moves the level away from the played action
+            if stim >= 0. {
+                prop_assert!(if coop { new >= level } else { new <= level });
+            }
+        }
+
+        #[test]
+        fn test_histogram_counts_everyone(levels in proptest::collection::vec(0f32..=1., 0..50)) {
+            let hist = level_histogram(levels.iter().copied());
+            prop_assert_eq!(hist.iter().sum::<usize>(), levels.len());
+        }
+    }
+
+    #[test]
+    fn test_histogram_edges() {
+        let hist = level_histogram([0., 0.05, 0.15, 1.].into_iter());
+        assert_eq!(hist[0], 2);
+        assert_eq!(hist[1], 1);
+        assert_eq!(hist[LEVEL_BINS - 1], 1);
+    }
+}
@@ -1,6 +1,21 @@
+pub mod assistance;
+pub mod behaviour;
+pub mod density;
 pub mod evacuee;
 pub mod evacuee_cell;
 pub mod fire_influence;
+pub mod firefighter;
+pub mod game;
+pub mod group;
+pub mod learning;
+pub mod mixed;
+pub mod neighbourhood;
+pub mod pressure;
+pub mod queue;
+pub mod reputation;
+pub mod speed;
 pub mod static_influence;
 pub mod strategies;
 pub mod strategy;
+pub mod update_scheme;
+pub mod warden;
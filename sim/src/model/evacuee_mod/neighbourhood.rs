@@ -0,0 +1,105 @@
+use serde::Deserialize;
+
+use crate::model::misc::misc_func::Loc;
+
+const VON_NEUMANN: [(i32, i32); 4] = [(0, 1), (1, 0), (-1, 0), (0, -1)];
+
+const MOORE: [(i32, i32); 8] = [
+    (0, 1),
+    (1, 0),
+    (-1, 0),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// Hexagonal neighbourhood emulated on the square grid using "odd-r" offset coordinates.
+/// Even rows are shifted to the left, odd rows to the right.
+const HEX_EVEN_ROW: [(i32, i32); 6] = [(1, 0), (-1, 0), (0, -1), (-1, -1), (0, 1), (-1, 1)];
+const HEX_ODD_ROW: [(i32, i32); 6] = [(1, 0), (-1, 0), (0, -1), (1, -1), (0, 1), (1, 1)];
+
+/// Set of cells an evacuee is allowed to move to in a single step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum Neighbourhood {
+    /// Four orthogonal moves
+    #[default]
+    VonNeumann,
+    /// Eight moves, diagonal steps cost sqrt(2)
+    Moore,
+    /// Six equidistant moves
+    Hexagonal,
+}
+
+impl Neighbourhood {
+    /// Offsets of the neighbouring cells relative to `loc`
+    pub fn offsets(&self, loc: &Loc) -> &'static [(i32, i32)] {
+        match self {
+            Neighbourhood::VonNeumann => &VON_NEUMANN,
+            Neighbourhood::Moore => &MOORE,
+            Neighbourhood::Hexagonal if loc.1.rem_euclid(2) == 0 => &HEX_EVEN_ROW,
+            Neighbourhood::Hexagonal => &HEX_ODD_ROW,
+        }
+    }
+
+    /// Cost of taking a step with the given offset
+    #[inline]
+    pub fn step_cost(&self, (i, j): (i32, i32)) -> f32 {
+        match self {
+            Neighbourhood::Moore if i != 0 && j != 0 => std::f32::consts::SQRT_2,
+            _ => 1.,
+        }
+    }
+
+    /// Neighbouring cells of `loc` paired with the cost of stepping into them
+    pub fn neighbours(&self, loc: &Loc) -> impl Iterator<Item = (Loc, f32)> + '_ {
+        let Loc(x, y) = *loc;
+        self.offsets(loc)
+            .iter()
+            .map(move |&(i, j)| (Loc(x + i, y + j), self.step_cost((i, j))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use itertools::Itertools;
+
+    #[test]
+    fn von_neumann_has_four_unit_moves() {
+        let n = Neighbourhood::VonNeumann
+            .neighbours(&Loc(3, 3))
+            .collect_vec();
+        assert_eq!(n.len(), 4);
+        assert!(n.iter().all(|(_, c)| *c == 1.));
+    }
+
+    #[test]
+    fn moore_diagonals_are_weighted() {
+        let n = Neighbourhood::Moore.neighbours(&Loc(3, 3)).collect_vec();
+        assert_eq!(n.len(), 8);
+        for (Loc(x, y), c) in n {
+            if x != 3 && y != 3 {
+                assert_relative_eq!(c, 2f32.sqrt());
+            } else {
+                assert_relative_eq!(c, 1.);
+            }
+        }
+    }
+
+    #[test]
+    fn hexagonal_neighbours_are_unique_and_symmetric() {
+        let hex = Neighbourhood::Hexagonal;
+        for origin in [Loc(4, 4), Loc(4, 5)] {
+            let n = hex.neighbours(&origin).map(|(l, _)| l).collect_vec();
+            assert_eq!(n.len(), 6);
+            assert_eq!(n.iter().unique().count(), 6);
+            // Every neighbour must also see the origin as its own neighbour
+            for l in n {
+                assert!(hex.neighbours(&l).any(|(o, _)| o == origin));
+            }
+        }
+    }
+}
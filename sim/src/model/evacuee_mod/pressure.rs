@@ -0,0 +1,145 @@
+use krabmaga::HashMap;
+use serde::Deserialize;
+
+use crate::model::misc::misc_func::Loc;
+
+/// Crowd pressure on the evacuees and the harm it causes
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct PressurePolicy {
+    /// Pressure above which an evacuee is injured
+    pub injury_threshold: f32,
+    /// Pressure above which an evacuee is incapacitated and counted as dead
+    pub incapacitation_threshold: f32,
+    /// Push of a blocked competitive evacuee relative to a cooperative one
+    #[serde(default = "default_competitive_weight")]
+    pub competitive_weight: f32,
+    /// Share of the pressure on an evacuee passed on to the one it presses against
+    #[serde(default)]
+    pub transmission: f32,
+    /// Distance to an exit within which crush casualties count as exit casualties
+    #[serde(default = "default_exit_radius")]
+    pub exit_radius: f32,
+}
+
+fn default_competitive_weight() -> f32 {
+    1.
+}
+
+fn default_exit_radius() -> f32 {
+    3.
+}
+
+/// Harm done by the pressure on an evacuee
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrushOutcome {
+    Unharmed,
+    Injured,
+    Incapacitated,
+}
+
+/// Evacuee trying to enter an occupied cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Presser {
+    pub from: Loc,
+    pub to: Loc,
+    pub competitive: bool,
+}
+
+impl PressurePolicy {
+    /// Pressure on every occupied cell from the `pressers` trying to enter it.
+    /// Every presser adds its weight plus the transmitted share of the pressure on its own cell,
+    /// so `pressers` must be sorted from the farthest to the closest to the exit
+    pub fn pressure(&self, pressers: &[Presser]) -> HashMap<Loc, f32> {
+        let mut pressure: HashMap<Loc, f32> = HashMap::new();
+        for p in pressers {
+            let weight = if p.competitive {
+                self.competitive_weight
+            } else {
+                1.
+            };
+            let behind = pressure.get(&p.from).copied().unwrap_or(0.);
+            *pressure.entry(p.to).or_default() += weight + self.transmission * behind;
+        }
+        pressure
+    }
+
+    pub fn outcome(&self, pressure: f32) -> CrushOutcome {
+        if pressure > self.incapacitation_threshold {
+            CrushOutcome::Incapacitated
+        } else if pressure > self.injury_threshold {
+            CrushOutcome::Injured
+        } else {
+            CrushOutcome::Unharmed
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn policy(transmission: f32) -> PressurePolicy {
+        PressurePolicy {
+            injury_threshold: 2.,
+            incapacitation_threshold: 4.,
+            competitive_weight: 2.,
+            transmission,
+            exit_radius: 3.,
+        }
+    }
+
+    fn presser(from: Loc, to: Loc, competitive: bool) -> Presser {
+        Presser {
+            from,
+            to,
+            competitive,
+        }
+    }
+
+    #[test]
+    fn test_direct_pressure() {
+        let front = Loc(5, 1);
+        let pressers = [
+            presser(Loc(4, 2), front, false),
+            presser(Loc(5, 2), front, true),
+            presser(Loc(6, 2), front, true),
+        ];
+        let pressure = policy(0.).pressure(&pressers);
+        assert_eq!(pressure[&front], 5.);
+        assert_eq!(pressure.len(), 1);
+        assert_eq!(
+            policy(0.).outcome(pressure[&front]),
+            CrushOutcome::Incapacitated
+        );
+    }
+
+    #[test]
+    fn test_transmitted_pressure() {
+        // A column of three evacuees behind the one at the front
+        let pressers = [
+            presser(Loc(5, 4), Loc(5, 3), false),
+            presser(Loc(5, 3), Loc(5, 2), false),
+            presser(Loc(5, 2), Loc(5, 1), true),
+        ];
+        let pressure = policy(0.5).pressure(&pressers);
+        assert_eq!(pressure[&Loc(5, 3)], 1.);
+        assert_eq!(pressure[&Loc(5, 2)], 1.5);
+        assert_eq!(pressure[&Loc(5, 1)], 2.75);
+        assert_eq!(policy(0.5).outcome(2.75), CrushOutcome::Injured);
+    }
+
+    proptest! {
+        #[test]
+        fn test_outcome_monotone(a in 0f32..10., b in 0f32..10.) {
+            let (low, high) = if a < b { (a, b) } else { (b, a) };
+            let rank = |o| match o {
+                CrushOutcome::Unharmed => 0,
+                CrushOutcome::Injured => 1,
+                CrushOutcome::Incapacitated => 2,
+            };
+            let p = policy(0.);
+            prop_assert!(rank(p.outcome(low)) <= rank(p.outcome(high)));
+        }
+    }
+}
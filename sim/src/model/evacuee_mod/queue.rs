@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+
+use krabmaga::HashMap;
+use serde::Deserialize;
+
+use crate::model::misc::misc_func::Loc;
+
+/// Orderly queueing of the evacuees close to an exit
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct QueuePolicy {
+    /// Distance to an exit within which evacuees join its queue
+    pub radius: f32,
+    /// Share of the cell reward lost by a competitive evacuee jumping ahead of the queue
+    #[serde(default)]
+    pub jump_cost: f32,
+}
+
+/// Implicit FIFO queues of the evacuees waiting at every exit
+#[derive(Debug, Clone)]
+pub struct ExitQueues {
+    pub policy: QueuePolicy,
+    /// Competitive evacuees that got a cell ahead of the queue since the start
+    pub jumps: u64,
+    /// Id and arrival step of the queued evacuees, in arrival order
+    queues: HashMap<Loc, VecDeque<(usize, u64)>>,
+    /// Squared distance to the exit of the queued evacuees at the last update
+    distances: HashMap<usize, i32>,
+    /// Waiting times of the evacuees that left the queue during the last update
+    waits: HashMap<Loc, Vec<u64>>,
+}
+
+impl ExitQueues {
+    pub fn new(policy: QueuePolicy) -> Self {
+        Self {
+            policy,
+            jumps: 0,
+            queues: HashMap::new(),
+            distances: HashMap::new(),
+            waits: HashMap::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.jumps = 0;
+        self.queues.clear();
+        self.distances.clear();
+        self.waits.clear();
+    }
+
+    /// Exit whose queue zone contains `loc`, the closest one if the zones overlap
+    pub fn zone(&self, exits: &[Loc], loc: Loc) -> Option<Loc> {
+        exits
+            .iter()
+            .map(|e| (*e, ((e.0 - loc.0).pow(2) + (e.1 - loc.1).pow(2)) as f32))
+            .filter(|(_, d)| *d <= self.policy.radius.powi(2))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(e, _)| e)
+    }
+
+    /// Refresh the queue of `exit` given the ids and squared distances to the exit of the evacuees
+    /// `present` in its zone at `step`.
+    /// Newcomers join at the back in the given order, the ones that left record their waiting time
+    pub fn update(&mut self, exit: Loc, present: &[(usize, i32)], step: u64) {
+        let queue = self.queues.entry(exit).or_default();
+        let waits = self.waits.entry(exit).or_default();
+        let distances = &mut self.distances;
+        waits.clear();
+        queue.retain(|(id, arrival)| {
+            let stays = present.iter().any(|(p, _)| p == id);
+            if !stays {
+                waits.push(step - arrival);
+                distances.remove(id);
+            }
+            stays
+        });
+        for (id, d) in present {
+            if !queue.iter().any(|(q, _)| q == id) {
+                queue.push_back((*id, step));
+            }
+            distances.insert(*id, *d);
+        }
+    }
+
+    /// Squared distance to `exit` of the evacuee waiting just ahead of `id` in its queue,
+    /// `None` if `id` is first or not queued
+    pub fn ahead(&self, exit: Loc, id: usize) -> Option<i32> {
+        let queue = self.queues.get(&exit)?;
+        let pos = queue.iter().position(|(q, _)| *q == id)?;
+        let (prev, _) = queue.get(pos.checked_sub(1)?)?;
+        self.distances.get(prev).copied()
+    }
+
+    /// Place of `id` in the queue of `exit`, `None` if it is not queued
+    pub fn position(&self, exit: Loc, id: usize) -> Option<usize> {
+        self.queues.get(&exit)?.iter().position(|(q, _)| *q == id)
+    }
+
+    pub fn len(&self, exit: Loc) -> usize {
+        self.queues.get(&exit).map_or(0, |q| q.len())
+    }
+
+    /// Mean waiting time of the evacuees that left the queue of `exit` during the last update
+    pub fn mean_wait(&self, exit: Loc) -> Option<f64> {
+        let waits = self.waits.get(&exit)?;
+        (!waits.is_empty()).then(|| waits.iter().sum::<u64>() as f64 / waits.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queues() -> ExitQueues {
+        ExitQueues::new(QueuePolicy {
+            radius: 3.,
+            jump_cost: 0.5,
+        })
+    }
+
+    #[test]
+    fn test_fifo_order() {
+        let exit = Loc(5, 10);
+        let mut q = queues();
+        q.update(exit, &[(4, 1), (2, 2)], 0);
+        q.update(exit, &[(1, 1), (2, 2), (4, 4)], 1);
+        assert_eq!(q.position(exit, 4), Some(0));
+        assert_eq!(q.position(exit, 2), Some(1));
+        assert_eq!(q.position(exit, 1), Some(2));
+        assert_eq!(q.position(exit, 3), None);
+        assert_eq!(q.len(exit), 3);
+        assert_eq!(q.mean_wait(exit), None);
+        assert_eq!(q.ahead(exit, 4), None);
+        assert_eq!(q.ahead(exit, 2), Some(4));
+        assert_eq!(q.ahead(exit, 1), Some(2));
+        assert_eq!(q.ahead(exit, 3), None);
+    }
+
+    #[test]
+    fn test_waiting_times() {
+        let exit = Loc(5, 10);
+        let mut q = queues();
+        q.update(exit, &[(1, 1), (2, 2)], 0);
+        q.update(exit, &[(2, 1), (3, 2)], 2);
+        q.update(exit, &[], 6);
+        // 1 waited 2 steps, then 2 waited 6 and 3 waited 4
+        assert_eq!(q.mean_wait(exit), Some(5.));
+        assert_eq!(q.len(exit), 0);
+        q.reset();
+        assert_eq!(q.mean_wait(exit), None);
+    }
+
+    #[test]
+    fn test_zone() {
+        let q = queues();
+        let exits = [Loc(0, 0), Loc(6, 0)];
+        assert_eq!(q.zone(&exits, Loc(2, 2)), Some(Loc(0, 0)));
+        assert_eq!(q.zone(&exits, Loc(4, 1)), Some(Loc(6, 0)));
+        assert_eq!(q.zone(&exits, Loc(3, 3)), None);
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use krabmaga::HashMap;
+use serde::Deserialize;
+
+use super::strategy::Strategy;
+
+/// Number of past encounters an evacuee remembers
+pub const DEFAULT_MEMORY: usize = 8;
+
+/// How an evacuee plays against opponents it remembers
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum ReciprocityRule {
+    /// Repeat the last move the opponents made against the evacuee
+    TitForTat,
+    /// Cooperate only with opponents that cooperated at least this share of the remembered encounters
+    Threshold(f32),
+}
+
+/// Bounded memory of every evacuee of the strategies its opponents played against it
+#[derive(Debug, Clone)]
+pub struct Reputation {
+    pub rule: ReciprocityRule,
+    /// Number of encounters remembered by every evacuee
+    pub capacity: usize,
+    memories: HashMap<usize, VecDeque<(usize, Strategy)>>,
+}
+
+impl Reputation {
+    pub fn new(rule: ReciprocityRule, capacity: usize) -> Self {
+        Self {
+            rule,
+            capacity,
+            memories: HashMap::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.memories.clear();
+    }
+
+    /// Remember that `opponent` played `strategy` against `id`
+    pub fn record(&mut self, id: usize, opponent: usize, strategy: Strategy) {
+        if self.capacity == 0 {
+            return;
+        }
+        let memory = self.memories.entry(id).or_default();
+        if memory.len() == self.capacity {
+            memory.pop_front();
+        }
+        memory.push_back((opponent, strategy));
+    }
+
+    /// Remember every move of a contest, `played` holds the id and strategy of every contestant
+    pub fn record_contest(&mut self, played: &[(usize, Strategy)]) {
+        for (id, _) in played {
+            for (opponent, strategy) in played.iter().filter(|(o, _)| o != id) {
+                self.record(*id, *opponent, *strategy);
+            }
+        }
+    }
+
+    /// Share of the remembered encounters in which `opponent` cooperated with `id`,
+    /// `None` if `id` does not remember `opponent`
+    pub fn reputation(&self, id: usize, opponent: usize) -> Option<f32> {
+        let (coops, total) = self
+            .memories
+            .get(&id)?
+            .iter()
+            .filter(|(o, _)| *o == opponent)
+            .fold((0, 0), |(c, t), (_, s)| {
+                (c + (*s == Strategy::Cooperative) as usize, t + 1)
+            });
+        (total != 0).then(|| coops as f32 / total as f32)
+    }
+
+    /// Last move `opponent` played against `id`
+    fn last_move(&self, id: usize, opponent: usize) -> Option<Strategy> {
+        self.memories
+            .get(&id)?
+            .iter()
+            .rev()
+            .find(|(o, _)| *o == opponent)
+            .map(|(_, s)| *s)
+    }
+
+    /// Strategy `id` plays against `opponents`. Competes if any remembered opponent is judged
+    /// untrustworthy, cooperates if all remembered opponents are trusted and keeps `current`
+    /// if nobody is remembered
+    pub fn choose(&self, id: usize, opponents: &[usize], current: Strategy) -> Strategy {
+        let trusted = opponents
+            .iter()
+            .filter_map(|o| match self.rule {
+                ReciprocityRule::TitForTat => {
+                    self.last_move(id, *o).map(|s| s == Strategy::Cooperative)
+                }
+                ReciprocityRule::Threshold(t) => self.reputation(id, *o).map(|r| r >= t),
+            })
+            .collect::<Vec<_>>();
+        if trusted.is_empty() {
+            current
+        } else if trusted.into_iter().all(|t| t) {
+            Strategy::Cooperative
+        } else {
+            Strategy::Competitive
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Strategy::*;
+
+    #[test]
+    fn test_memory_is_bounded() {
+        let mut rep = Reputation::new(ReciprocityRule::TitForTat, 2);
+        rep.record(0, 1, Competitive);
+        rep.record(0, 2, Cooperative);
+        rep.record(0, 2, Cooperative);
+        // the encounter with 1 has been forgotten
+        assert_eq!(rep.reputation(0, 1), None);
+        assert_eq!(rep.reputation(0, 2), Some(1.));
+    }
+
+    #[test]
+    fn test_record_contest() {
+        let mut rep = Reputation::new(ReciprocityRule::TitForTat, DEFAULT_MEMORY);
+        rep.record_contest(&[(0, Competitive), (1, Cooperative)]);
+        assert_eq!(rep.reputation(0, 1), Some(1.));
+        assert_eq!(rep.reputation(1, 0), Some(0.));
+        assert_eq!(rep.reputation(0, 0), None);
+    }
+
+    #[test]
+    fn test_tit_for_tat() {
+        let mut rep = Reputation::new(ReciprocityRule::TitForTat, DEFAULT_MEMORY);
+        assert_eq!(rep.choose(0, &[1], Competitive), Competitive);
+        rep.record(0, 1, Competitive);
+        rep.record(0, 1, Cooperative);
+        assert_eq!(rep.choose(0, &[1], Competitive), Cooperative);
+        rep.record(0, 2, Competitive);
+        assert_eq!(rep.choose(0, &[1, 2], Cooperative), Competitive);
+        // unknown opponents do not matter
+        assert_eq!(rep.choose(0, &[1, 3], Competitive), Cooperative);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let mut rep = Reputation::new(ReciprocityRule::Threshold(0.5), DEFAULT_MEMORY);
+        rep.record(0, 1, Competitive);
+        rep.record(0, 1, Cooperative);
+        rep.record(0, 2, Competitive);
+        rep.record(0, 2, Competitive);
+        rep.record(0, 2, Cooperative);
+        assert_eq!(rep.choose(0, &[1], Competitive), Cooperative);
+        assert_eq!(rep.choose(0, &[2], Cooperative), Competitive);
+    }
+}
@@ -0,0 +1,176 @@
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use super::{evacuee_cell::default_strength, learning::LearningModel};
+
+/// Fastest custom speed in cells per tick, every cell is a sub-step of the tick
+pub const MAX_SPEED: f32 = 10.;
+
+/// Walking speed profile of an evacuee, relative to an unimpeded adult (1 cell per tick)
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum SpeedProfile {
+    Adult,
+    Child,
+    Elderly,
+    MobilityImpaired,
+    /// Speed in cells per tick
+    Custom(f32),
+}
+
+impl SpeedProfile {
+    /// Cells covered per tick
+    pub fn speed(&self) -> f32 {
+        match self {
+            SpeedProfile::Adult => 1.,
+            SpeedProfile::Child => 0.75,
+            SpeedProfile::Elderly => 0.7,
+            SpeedProfile::MobilityImpaired => 0.45,
+            SpeedProfile::Custom(s) => s.max(0.),
+        }
+    }
+}
+
+/// Class of evacuees sharing the same speed profile
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct AgentClass {
+    pub profile: SpeedProfile,
+    /// Relative share of the population, does not need to sum up to one
+    pub proportion: f32,
+    /// Strength of the members when arguing over a cell
+    #[serde(default = "default_strength")]
+    pub strength: f32,
+    /// Learning rule of the members, the one of the setup if missing
+    pub learning: Option<LearningModel>,
+}
+
+impl AgentClass {
+    /// Check the class can be simulated, custom speeds must be finite and at most `MAX_SPEED`
+    pub fn validate(&self) -> Result<(), String> {
+        match self.profile {
+            SpeedProfile::Custom(s) if s.is_nan() || s > MAX_SPEED => Err(format!(
+                "custom speed {s} is not at most {MAX_SPEED} cells per tick"
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Run the sub-steps of a tick. `substep` moves the evacuees once, adding the movement of the tick
+/// to their budget when told it is the first sub-step, and returns whether any of them has a sub-step left.
+/// Returns the number of sub-steps taken, at least one
+pub fn run_substeps(mut substep: impl FnMut(bool) -> bool) -> usize {
+    let mut taken = 1;
+    while substep(taken == 1) {
+        taken += 1;
+    }
+    taken
+}
+
+/// Sample the class index of a new evacuee given the class proportions
+/// Returns 0 if no classes are defined
+pub fn sample_class(classes: &[AgentClass], rng: &mut dyn RngCore) -> usize {
+    if classes.is_empty() {
+        return 0;
+    }
+    WeightedIndex::new(classes.iter().map(|c| c.proportion))
+        .map(|dist| dist.sample(rng))
+        .expect("Agent class proportions are not valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::evacuee_mod::evacuee_cell::EvacueeCell;
+    use itertools::Itertools;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn profiles_are_slower_than_adults() {
+        let adult = SpeedProfile::Adult.speed();
+        for p in [
+            SpeedProfile::Child,
+            SpeedProfile::Elderly,
+            SpeedProfile::MobilityImpaired,
+        ] {
+            assert!(p.speed() < adult);
+        }
+        assert_eq!(SpeedProfile::Custom(2.).speed(), 2.);
+        assert_eq!(SpeedProfile::Custom(-1.).speed(), 0.);
+    }
+
+    #[test]
+    fn custom_speed_is_bounded() {
+        let class = |s| AgentClass {
+            profile: SpeedProfile::Custom(s),
+            proportion: 1.,
+            strength: 1.,
+            learning: None,
+        };
+        assert!(class(MAX_SPEED).validate().is_ok());
+        assert!(class(2.).validate().is_ok());
+        assert!(class(MAX_SPEED + 1.).validate().is_err());
+        assert!(class(f32::INFINITY).validate().is_err());
+        assert!(class(f32::NAN).validate().is_err());
+    }
+
+    #[test]
+    fn substeps_follow_the_speed() {
+        let tick = |evac: &mut EvacueeCell| {
+            let mut moves = 0;
+            let taken = run_substeps(|new_tick| {
+                if new_tick {
+                    evac.accumulate_budget();
+                }
+                if evac.can_move() {
+                    evac.spend_substep();
+                    moves += 1;
+                }
+                evac.can_move()
+            });
+            (taken, moves)
+        };
+        let mut fast = EvacueeCell {
+            speed: 2.5,
+            ..Default::default()
+        };
+        // The half cell left over is banked for the next tick
+        let ticks = (0..4).map(|_| tick(&mut fast)).collect_vec();
+        assert_eq!(ticks, vec![(2, 2), (3, 3), (2, 2), (3, 3)]);
+        let mut slow = EvacueeCell {
+            speed: 0.5,
+            ..Default::default()
+        };
+        let ticks = (0..4).map(|_| tick(&mut slow)).collect_vec();
+        assert_eq!(ticks, vec![(1, 0), (1, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn no_classes_default_to_first() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        assert_eq!(sample_class(&[], &mut rng), 0);
+    }
+
+    #[test]
+    fn sample_class_follows_proportions() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let classes = [
+            AgentClass {
+                profile: SpeedProfile::Adult,
+                proportion: 1.,
+                strength: 1.,
+                learning: None,
+            },
+            AgentClass {
+                profile: SpeedProfile::Child,
+                proportion: 0.,
+                strength: 1.,
+                learning: None,
+            },
+        ];
+        let sampled = (0..100)
+            .map(|_| sample_class(&classes, &mut rng))
+            .collect_vec();
+        assert!(sampled.into_iter().all(|c| c == 0));
+    }
+}
@@ -1,9 +1,22 @@
 use mockall::predicate::*;
 use mockall::*;
 
+use crate::model::lerp::{expression::Expression, table::PiecewiseCurve};
+
 #[automock]
 pub trait AspirationStrategy {
-    fn calculate_asp(&self, numb_cells: usize) -> f32;
+    /// Global aspiration given the number of cells on fire and the current step
+    fn calculate_asp(&self, numb_cells: usize, step: u64) -> f32;
+
+    /// Aspiration of an evacuee whose own aspiration level is `own`, the global aspiration by default
+    fn agent_asp(&self, numb_cells: usize, step: u64, _own: f32) -> f32 {
+        self.calculate_asp(numb_cells, step)
+    }
+
+    /// Own aspiration level of an evacuee after receiving `payoff`, unchanged by default
+    fn habituate(&self, own: f32, _payoff: f32) -> f32 {
+        own
+    }
 }
 
 pub struct LogAspManip(pub f32);
@@ -15,7 +28,7 @@ impl Default for LogAspManip {
 }
 
 impl AspirationStrategy for LogAspManip {
-    fn calculate_asp(&self, numb_cells: usize) -> f32 {
+    fn calculate_asp(&self, numb_cells: usize, _step: u64) -> f32 {
         // Use ln + 1 to avoid inf cases
         self.0 * (numb_cells as f32).ln_1p()
     }
@@ -30,18 +43,159 @@ impl Default for RootAsp {
 }
 
 impl AspirationStrategy for RootAsp {
-    fn calculate_asp(&self, numb_cells: usize) -> f32 {
+    fn calculate_asp(&self, numb_cells: usize, _step: u64) -> f32 {
         self.0 * (numb_cells as f32).sqrt()
     }
 }
 
+/// Aspiration level reached by the saturating aspirations with a unit factor
+pub const ASP_SCALE: f32 = 10.;
+
+pub struct LinearAsp(pub f32);
+
+impl Default for LinearAsp {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl AspirationStrategy for LinearAsp {
+    fn calculate_asp(&self, numb_cells: usize, _step: u64) -> f32 {
+        self.0 * numb_cells as f32
+    }
+}
+
+/// Exponential saturation
+/// $$
+/// asp = factor ASP_SCALE (1 - e^{-rate n})
+/// $$
+pub struct ExpSatAsp {
+    pub factor: f32,
+    pub rate: f32,
+}
+
+impl Default for ExpSatAsp {
+    fn default() -> Self {
+        Self {
+            factor: 1.,
+            rate: 0.01,
+        }
+    }
+}
+
+impl AspirationStrategy for ExpSatAsp {
+    fn calculate_asp(&self, numb_cells: usize, _step: u64) -> f32 {
+        self.factor * ASP_SCALE * -(-self.rate * numb_cells as f32).exp_m1()
+    }
+}
+
+/// Logistic curve centered on `midpoint` cells on fire
+/// $$
+/// asp = factor ASP_SCALE / (1 + e^{-rate (n - midpoint)})
+/// $$
+pub struct LogisticAsp {
+    pub factor: f32,
+    pub rate: f32,
+    pub midpoint: f32,
+}
+
+impl Default for LogisticAsp {
+    fn default() -> Self {
+        Self {
+            factor: 1.,
+            rate: 0.01,
+            midpoint: 500.,
+        }
+    }
+}
+
+impl AspirationStrategy for LogisticAsp {
+    fn calculate_asp(&self, numb_cells: usize, _step: u64) -> f32 {
+        self.factor * ASP_SCALE / (1. + (-self.rate * (numb_cells as f32 - self.midpoint)).exp())
+    }
+}
+
+/// Tabulated aspiration as a function of the fire area
+impl AspirationStrategy for PiecewiseCurve {
+    fn calculate_asp(&self, numb_cells: usize, _step: u64) -> f32 {
+        self.eval(numb_cells as f32)
+    }
+}
+
+/// Aspiration given by an expression of the fire area `area` and the current `step`
+pub struct ExpressionAsp(pub Expression);
+
+impl ExpressionAsp {
+    pub const VARIABLES: [&'static str; 2] = ["area", "step"];
+}
+
+impl AspirationStrategy for ExpressionAsp {
+    fn calculate_asp(&self, numb_cells: usize, step: u64) -> f32 {
+        self.0.eval(&[numb_cells as f32, step as f32])
+    }
+}
+
+/// Aspiration growing with the time elapsed instead of the fire area
+pub struct StepAsp(pub f32);
+
+impl Default for StepAsp {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl AspirationStrategy for StepAsp {
+    fn calculate_asp(&self, _numb_cells: usize, step: u64) -> f32 {
+        self.0 * (step as f32).ln_1p()
+    }
+}
+
+/// Aspiration learnt by every evacuee from its own payoffs (habituation)
+/// $$
+/// a_{t+1} = a_t + rate (payoff_t - a_t)
+/// $$
+/// optionally blended with a global fire driven aspiration
+/// $$
+/// asp = weight global + (1 - weight) a_t
+/// $$
+/// Own aspiration levels start at zero.
+pub struct Habituation {
+    pub rate: f32,
+    /// Weight of the global aspiration
+    pub weight: f32,
+    pub global: Option<Box<dyn AspirationStrategy + Send>>,
+}
+
+impl AspirationStrategy for Habituation {
+    fn calculate_asp(&self, numb_cells: usize, step: u64) -> f32 {
+        self.global
+            .as_ref()
+            .map_or(0., |g| g.calculate_asp(numb_cells, step))
+    }
+
+    fn agent_asp(&self, numb_cells: usize, step: u64, own: f32) -> f32 {
+        match &self.global {
+            Some(g) => self.weight * g.calculate_asp(numb_cells, step) + (1. - self.weight) * own,
+            None => own,
+        }
+    }
+
+    fn habituate(&self, own: f32, payoff: f32) -> f32 {
+        own + self.rate * (payoff - own)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use approx::assert_relative_eq;
 
     use crate::model::evacuee_mod::strategies::aspiration_strategy::AspirationStrategy;
 
-    use super::{LogAspManip, RootAsp};
+    use super::{
+        ExpSatAsp, ExpressionAsp, Habituation, LinearAsp, LogAspManip, LogisticAsp, RootAsp,
+        StepAsp, ASP_SCALE,
+    };
+    use crate::model::lerp::expression::Expression;
 
     #[test]
     fn check_default_strategies() {
@@ -62,13 +216,62 @@ mod tests {
         let default_log = LogAspManip::default();
 
         assert_relative_eq!(
-            default_root.calculate_asp(number_of_cells),
+            default_root.calculate_asp(number_of_cells, 0),
             (number_of_cells as f32).sqrt() / 3.
         );
 
         assert_relative_eq!(
-            default_log.calculate_asp(number_of_cells),
+            default_log.calculate_asp(number_of_cells, 0),
             (number_of_cells as f32).ln_1p() / 3.
         )
     }
+
+    #[test]
+    fn check_habituation() {
+        let own = Habituation {
+            rate: 0.5,
+            weight: 0.,
+            global: None,
+        };
+        assert_relative_eq!(own.habituate(1., 3.), 2.);
+        assert_relative_eq!(own.agent_asp(50, 0, 2.), 2.);
+        assert_relative_eq!(own.calculate_asp(50, 0), 0.);
+
+        let blended = Habituation {
+            rate: 0.5,
+            weight: 0.25,
+            global: Some(Box::new(RootAsp(1.))),
+        };
+        assert_relative_eq!(blended.agent_asp(16, 0, 2.), 0.25 * 4. + 0.75 * 2.);
+        assert_relative_eq!(blended.calculate_asp(16, 0), 4.);
+        // global strategies do not adapt
+        assert_relative_eq!(RootAsp(1.).habituate(1., 3.), 1.);
+        assert_relative_eq!(RootAsp(1.).agent_asp(16, 0, 2.), 4.);
+    }
+
+    #[test]
+    fn check_expression() {
+        let src = "0.3*ln(1+area) + step";
+        let asp = ExpressionAsp(Expression::parse(src, &ExpressionAsp::VARIABLES).unwrap());
+        assert_relative_eq!(asp.calculate_asp(9, 2), 0.3 * 10f32.ln() + 2.);
+    }
+
+    #[test]
+    fn check_new_shapes() {
+        assert_relative_eq!(LinearAsp(0.5).calculate_asp(10, 0), 5.);
+
+        let exp = ExpSatAsp::default();
+        assert_relative_eq!(exp.calculate_asp(0, 0), 0.);
+        assert!(exp.calculate_asp(100, 0) < exp.calculate_asp(200, 0));
+        assert!(exp.calculate_asp(100_000, 0) <= ASP_SCALE);
+
+        let logistic = LogisticAsp::default();
+        assert_relative_eq!(logistic.calculate_asp(500, 0), ASP_SCALE / 2.);
+        assert!(logistic.calculate_asp(100_000, 0) <= ASP_SCALE);
+
+        // only time based aspirations depend on the step
+        assert_relative_eq!(StepAsp(2.).calculate_asp(50, 0), 0.);
+        assert_relative_eq!(StepAsp(2.).calculate_asp(0, 9), 2. * 10f32.ln());
+        assert_relative_eq!(LinearAsp(1.).calculate_asp(3, 9), 3.);
+    }
 }
@@ -1,49 +1,94 @@
-// use mockall::predicate::*;
-// use mockall::*;
-
-// #[automock]
-// pub trait RatioStrategy {
-//     fn calculate_ratio(&self, fire_d: f32) -> f32;
-// }
-
-// pub struct RootDist(pub f32);
-
-// impl Default for RootDist {
-//     fn default() -> Self {
-//         Self(1.)
-//     }
-// }
-
-// impl RatioStrategy for RootDist {
-//     fn calculate_ratio(&self, fire_d: f32) -> f32 {
-//         fire_d.sqrt() * self.0
-//     }
-// }
-
-// pub struct LogDist(pub f32);
-
-// impl Default for LogDist {
-//     fn default() -> Self {
-//         Self(1.)
-//     }
-// }
-
-// impl RatioStrategy for LogDist {
-//     fn calculate_ratio(&self,fire_d:f32) -> f32 {
-//         fire_d.ln_1p() * self.0
-//     }
-// }
-
-// pub struct IDdist(pub f32);
-
-// impl Default for IDdist {
-//     fn default() -> Self {
-//         Self(1.)
-//     }
-// }
-
-// impl RatioStrategy for IDdist {
-//     fn calculate_ratio(&self,fire_d:f32) -> f32 {
-//         fire_d * self.0
-//     }
-// }
+use mockall::predicate::*;
+use mockall::*;
+
+use crate::model::lerp::{
+    equations::LerpStruct, expression::ExpressionCurve, table::PiecewiseCurve,
+};
+
+#[automock]
+pub trait RatioStrategy {
+    fn calculate_ratio(&self, fire_d: f32) -> f32;
+}
+
+impl RatioStrategy for LerpStruct {
+    fn calculate_ratio(&self, fire_d: f32) -> f32 {
+        self.eval(fire_d)
+    }
+}
+
+impl RatioStrategy for PiecewiseCurve {
+    fn calculate_ratio(&self, fire_d: f32) -> f32 {
+        self.eval(fire_d)
+    }
+}
+
+impl RatioStrategy for ExpressionCurve {
+    fn calculate_ratio(&self, fire_d: f32) -> f32 {
+        self.eval(fire_d)
+    }
+}
+
+pub struct RootDist(pub f32);
+
+impl Default for RootDist {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl RatioStrategy for RootDist {
+    fn calculate_ratio(&self, fire_d: f32) -> f32 {
+        fire_d.sqrt() * self.0
+    }
+}
+
+pub struct LogDist(pub f32);
+
+impl Default for LogDist {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl RatioStrategy for LogDist {
+    fn calculate_ratio(&self, fire_d: f32) -> f32 {
+        fire_d.ln_1p() * self.0
+    }
+}
+
+pub struct IDdist(pub f32);
+
+impl Default for IDdist {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl RatioStrategy for IDdist {
+    fn calculate_ratio(&self, fire_d: f32) -> f32 {
+        fire_d * self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::model::lerp::equations::{Equation, LerpStruct};
+
+    use super::{IDdist, LogDist, RatioStrategy, RootDist};
+
+    #[test]
+    fn check_calculations() {
+        let dist = 16.;
+        assert_relative_eq!(RootDist::default().calculate_ratio(dist), 4.);
+        assert_relative_eq!(LogDist(2.).calculate_ratio(dist), 2. * 17f32.ln());
+        assert_relative_eq!(IDdist(0.5).calculate_ratio(dist), 8.);
+    }
+
+    #[test]
+    fn check_lerp_matches_eval() {
+        let lerp = LerpStruct::new(0., 100., 0., 20., 1., Equation::Linear);
+        assert_relative_eq!(lerp.calculate_ratio(25.), lerp.eval(25.));
+    }
+}
@@ -1,42 +1,88 @@
-// use mockall::predicate::*;
-// use mockall::*;
-
-// #[automock]
-// pub trait RewardStrategy {
-//     fn calculate_reward(&self, exit_dist: f32) -> f32;
-// }
-
-// ///Inverse Log Square root calculates the following function
-// /// $f(x) = 1 + (ln 10) / ln(1 + sqrt(x))$
-// pub struct InverseLogRoot(pub f32);
-
-// impl Default for InverseLogRoot {
-//     fn default() -> Self {
-//         Self(1.)
-//     }
-// }
-
-// impl RewardStrategy for InverseLogRoot {
-//     fn calculate_reward(&self, exit_dist: f32) -> f32 {
-//         let calc = 1. + ((exit_dist + 1.) / 5.).ln_1p().recip();
-//         calc * self.0
-//     }
-// }
-
-// /// Self.0 denotes the effect of the reward
-// /// Self.1 denotes the max dist
-// /// So in this case it would be (max_h^2 + max_w^2).sqrt
-// pub struct RootReward(pub f32, pub f32);
-
-// impl Default for RootReward {
-//     fn default() -> Self {
-//         Self(1., 1.)
-//     }
-// }
-
-// impl RewardStrategy for RootReward {
-//     fn calculate_reward(&self, exit_dist: f32) -> f32 {
-//         let norm_const = 1. + (0.4f32).ln_1p().recip();
-//         self.0 * self.1 * (1. - exit_dist / norm_const)
-//     }
-// }
+use mockall::predicate::*;
+use mockall::*;
+
+use crate::model::lerp::{
+    equations::LerpStruct, expression::ExpressionCurve, table::PiecewiseCurve,
+};
+
+#[automock]
+pub trait RewardStrategy {
+    fn calculate_reward(&self, exit_dist: f32) -> f32;
+}
+
+impl RewardStrategy for LerpStruct {
+    fn calculate_reward(&self, exit_dist: f32) -> f32 {
+        self.eval(exit_dist)
+    }
+}
+
+impl RewardStrategy for PiecewiseCurve {
+    fn calculate_reward(&self, exit_dist: f32) -> f32 {
+        self.eval(exit_dist)
+    }
+}
+
+impl RewardStrategy for ExpressionCurve {
+    fn calculate_reward(&self, exit_dist: f32) -> f32 {
+        self.eval(exit_dist)
+    }
+}
+
+///Inverse Log Square root calculates the following function
+/// $f(x) = 1 + (ln 10) / ln(1 + sqrt(x))$
+pub struct InverseLogRoot(pub f32);
+
+impl Default for InverseLogRoot {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl RewardStrategy for InverseLogRoot {
+    fn calculate_reward(&self, exit_dist: f32) -> f32 {
+        let calc = 1. + ((exit_dist + 1.) / 5.).ln_1p().recip();
+        calc * self.0
+    }
+}
+
+/// Self.0 denotes the effect of the reward
+/// Self.1 denotes the max dist
+/// So in this case it would be (max_h^2 + max_w^2).sqrt
+pub struct RootReward(pub f32, pub f32);
+
+impl Default for RootReward {
+    fn default() -> Self {
+        Self(1., 1.)
+    }
+}
+
+impl RewardStrategy for RootReward {
+    fn calculate_reward(&self, exit_dist: f32) -> f32 {
+        let norm_const = 1. + (0.4f32).ln_1p().recip();
+        self.0 * self.1 * (1. - exit_dist / norm_const)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::model::lerp::equations::{Equation, LerpStruct};
+
+    use super::{InverseLogRoot, RewardStrategy, RootReward};
+
+    #[test]
+    fn check_rewards_decrease_with_distance() {
+        let inv = InverseLogRoot::default();
+        let root = RootReward(1., 50.);
+        assert!(inv.calculate_reward(1.) > inv.calculate_reward(10.));
+        assert!(root.calculate_reward(1.) > root.calculate_reward(10.));
+        assert_relative_eq!(root.calculate_reward(0.), 50.);
+    }
+
+    #[test]
+    fn check_lerp_matches_eval() {
+        let lerp = LerpStruct::new(0., 100., 20., 0., 1., Equation::Linear);
+        assert_relative_eq!(lerp.calculate_reward(25.), lerp.eval(25.));
+    }
+}
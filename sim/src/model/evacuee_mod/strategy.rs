@@ -1,10 +1,14 @@
 use krabmaga::log;
-use rand::{seq::SliceRandom, RngCore};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    seq::SliceRandom,
+    RngCore,
+};
 use rand_derive2::RandGen;
 use serde::Deserialize;
 use std::{borrow::BorrowMut, cmp::Ordering, fmt};
 
-use super::evacuee_cell::EvacueeCell;
+use super::{behaviour::Behaviour, evacuee_cell::EvacueeCell, game::ArgumentResolution};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum RuleCase {
@@ -40,26 +44,27 @@ pub fn s_x(rewards: RSTP, a_x: f32, reward: f32) -> f32 {
     (reward - a_x) / mx
 }
 
+/// Stimulus, payoff and the evacuee that received them
+pub type Outcome = (f32, f32, EvacueeCell);
+
+/// Resolve a contest, `asp` gives the aspiration of every contestant.
+/// `Ok` if the first evacuee gets the cell, `Err` if nobody does
 pub fn rules(
     game_rules: RuleCase,
     mut competing: Vec<(RSTP, EvacueeCell)>,
     rng: &mut impl RngCore,
-    asp: f32,
+    asp: impl Fn(&EvacueeCell) -> f32 + 'static,
+    resolution: ArgumentResolution,
     // ) -> Result<Vec<(f32, EvacueeCell)>, Vec<(f32, EvacueeCell)>> {
-) -> Result<
-    Box<dyn Iterator<Item = (f32, EvacueeCell)>>,
-    Box<dyn Iterator<Item = (f32, EvacueeCell)>>,
-> {
+) -> Result<Box<dyn Iterator<Item = Outcome>>, Box<dyn Iterator<Item = Outcome>>> {
     match game_rules {
         RuleCase::AllCoop => {
-            // if everyone is cooperating randomly shuffle the list
+            // if everyone is cooperating randomly shuffle the list, altruists yield to everyone else
             competing.shuffle(&mut *rng.borrow_mut());
-            Ok(Box::new(
-                competing
-                    .into_iter()
-                    .map(move |(rstp, e)| (s_x(rstp, asp, rstp.0), e)), // .collect::<Vec<_>>())
-                                                                        // .into_iter(),
-            ))
+            competing.sort_by_key(|(_, e)| e.behaviour == Behaviour::Altruist);
+            Ok(Box::new(competing.into_iter().map(move |(rstp, e)| {
+                (s_x(rstp, asp(&e), rstp.0), rstp.0, e)
+            })))
         } // any will do
         RuleCase::AllButOneCoop => {
             // put the competitive guy first and the rest second
@@ -75,14 +80,39 @@ pub fn rules(
                     } else {
                         w.1
                     };
-                    (s_x(w, asp, ret_w), el)
+                    (s_x(w, asp(&el), ret_w), ret_w, el)
                 }), // .into_iter(),
             ))
         }
+        RuleCase::Argument if resolution.has_winner() => {
+            // a competitive evacuee wins the fight, the rest fall back to the punishment or sucker payoff
+            competing.shuffle(&mut *rng.borrow_mut());
+            competing.sort_by_key(|(_, e)| e.strategy != Strategy::Competitive);
+            if resolution.strength_weighted() {
+                let n_comp = competing
+                    .iter()
+                    .take_while(|(_, e)| e.strategy == Strategy::Competitive)
+                    .count();
+                if let Ok(dist) =
+                    WeightedIndex::new(competing[..n_comp].iter().map(|(_, e)| e.strength.max(0.)))
+                {
+                    let winner = dist.sample(rng);
+                    competing.swap(0, winner);
+                }
+            }
+            Ok(Box::new(competing.into_iter().map(move |(w, el)| {
+                let ret_w = if el.strategy == Strategy::Competitive {
+                    w.3
+                } else {
+                    w.1
+                };
+                (s_x(w, asp(&el), ret_w), ret_w, el)
+            })))
+        }
         RuleCase::Argument => Err(Box::new(
             competing.into_iter().map(move |(w, el)| {
                 let retw = w.3;
-                (s_x(w, asp, retw), el)
+                (s_x(w, asp(&el), retw), retw, el)
             }), // .into_iter(),
         )),
         // .collect()),
@@ -192,6 +222,7 @@ mod tests {
                         strategy: Strategy::Cooperative,
                         pr_c: 0.,
                         pr_d: 0.,
+                        ..Default::default()
                     },
                 ),
                 (
@@ -202,13 +233,20 @@ mod tests {
                         strategy: Strategy::Cooperative,
                         pr_c: 0.,
                         pr_d: 0.,
+                        ..Default::default()
                     },
                 ),
             ];
-            let res = rules(game_rules, competing.clone(), &mut rng, asp)
-                .ok()
-                .unwrap()
-                .collect_vec();
+            let res = rules(
+                game_rules,
+                competing.clone(),
+                &mut rng,
+                move |_| asp,
+                ArgumentResolution::Block,
+            )
+            .ok()
+            .unwrap()
+            .collect_vec();
             let expected = vec![-0.33333334, -0.33333334];
             assert!(expected
                 .into_iter()
@@ -230,6 +268,7 @@ mod tests {
                         strategy: Strategy::Competitive,
                         pr_c: 0.,
                         pr_d: 0.,
+                        ..Default::default()
                     },
                 ),
                 (
@@ -240,13 +279,20 @@ mod tests {
                         strategy: Strategy::Cooperative,
                         pr_c: 0.,
                         pr_d: 0.,
+                        ..Default::default()
                     },
                 ),
             ];
-            let res = rules(game_rules, competing.clone(), &mut rng, asp)
-                .ok()
-                .unwrap()
-                .collect_vec();
+            let res = rules(
+                game_rules,
+                competing.clone(),
+                &mut rng,
+                move |_| asp,
+                ArgumentResolution::Block,
+            )
+            .ok()
+            .unwrap()
+            .collect_vec();
             let expected = vec![-1., -0.88888896];
             assert!(expected
                 .into_iter()
@@ -268,6 +314,7 @@ mod tests {
                         strategy: Strategy::Competitive,
                         pr_c: 0.,
                         pr_d: 0.,
+                        ..Default::default()
                     },
                 ),
                 (
@@ -278,18 +325,117 @@ mod tests {
                         strategy: Strategy::Competitive,
                         pr_c: 0.,
                         pr_d: 0.,
+                        ..Default::default()
                     },
                 ),
             ];
-            let res = dbg!(rules(game_rules, competing.clone(), &mut rng, asp)
-                .err()
-                .unwrap()
-                .collect_vec());
+            let res = dbg!(rules(
+                game_rules,
+                competing.clone(),
+                &mut rng,
+                move |_| asp,
+                ArgumentResolution::Block,
+            )
+            .err()
+            .unwrap()
+            .collect_vec());
             let expected = vec![1., 0.22222228];
             assert!(expected
                 .into_iter()
                 .zip(res.into_iter())
                 .all(|(a1, a2)| (a1 as f32) == a2.0));
         }
+
+        #[test]
+        fn test_rules_random_winner() {
+            let mut rng = ChaChaRng::seed_from_u64(3);
+            let evac = |x, strategy| EvacueeCell {
+                x,
+                y: 0,
+                strategy,
+                ..Default::default()
+            };
+            let competing = vec![
+                ((0.7, 0.2, 0.1, -0.5), evac(0, Strategy::Cooperative)),
+                ((0.7, 0.2, 0.1, -0.5), evac(1, Strategy::Competitive)),
+                ((0.7, 0.2, 0.1, -0.5), evac(2, Strategy::Competitive)),
+            ];
+            let res = rules(
+                RuleCase::Argument,
+                competing,
+                &mut rng,
+                |_| 1.,
+                ArgumentResolution::RandomWinner,
+            )
+            .ok()
+            .unwrap()
+            .collect_vec();
+            assert_eq!(res.len(), 3);
+            // competitive evacuees come first and the cooperative one last
+            assert_eq!(res[0].2.strategy, Strategy::Competitive);
+            assert_eq!(res[2].2.strategy, Strategy::Cooperative);
+            assert_eq!(res[0].0, res[1].0);
+            // the competitive evacuees get the punishment and the cooperative one the sucker payoff
+            assert_eq!(res[0].1, -0.5);
+            assert_eq!(res[2].1, 0.2);
+        }
+
+        #[test]
+        fn test_rules_altruist_yields() {
+            let mut rng = ChaChaRng::seed_from_u64(3);
+            let evac = |x, behaviour| EvacueeCell {
+                x,
+                y: 0,
+                strategy: Strategy::Cooperative,
+                behaviour,
+                ..Default::default()
+            };
+            for _ in 0..20 {
+                let competing = vec![
+                    ((0.7, 0.2, 0.1, 0.1), evac(0, Behaviour::Altruist)),
+                    ((0.7, 0.2, 0.1, 0.1), evac(1, Behaviour::Adaptive)),
+                ];
+                let mut res = rules(
+                    RuleCase::AllCoop,
+                    competing,
+                    &mut rng,
+                    |_| 1.,
+                    ArgumentResolution::Block,
+                )
+                .ok()
+                .unwrap();
+                assert_eq!(res.next().unwrap().2.x, 1);
+            }
+        }
+
+        #[test]
+        fn test_rules_weighted_winner() {
+            let mut rng = ChaChaRng::seed_from_u64(3);
+            let evac = |x, strength| EvacueeCell {
+                x,
+                y: 0,
+                strategy: Strategy::Competitive,
+                strength,
+                ..Default::default()
+            };
+            for _ in 0..20 {
+                let competing = vec![
+                    ((0.7, 0.2, 0.1, -0.5), evac(0, 0.)),
+                    ((0.7, 0.2, 0.1, -0.5), evac(1, 1.)),
+                    ((0.7, 0.2, 0.1, -0.5), evac(2, 0.)),
+                ];
+                let mut res = rules(
+                    RuleCase::Argument,
+                    competing,
+                    &mut rng,
+                    |_| 1.,
+                    ArgumentResolution::WeightedWinner,
+                )
+                .ok()
+                .unwrap();
+                // only the strong evacuee can win
+                assert_eq!(res.next().unwrap().2.x, 1);
+            }
+        }
     }
 }
@@ -0,0 +1,342 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use krabmaga::HashMap;
+use rand::{seq::SliceRandom, Rng, RngCore};
+use serde::Deserialize;
+
+use super::evacuee_cell::EvacueeCell;
+use crate::model::misc::misc_func::Loc;
+
+/// Update discipline of the evacuees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum UpdateScheme {
+    /// Everyone moves at once, conflicts over a cell are resolved by playing a game
+    #[default]
+    Parallel,
+    /// As many evacuees as there are on the grid are drawn with replacement and moved one at a time.
+    /// Evacuees that are not drawn skip the sub-step
+    RandomSequential,
+    /// Evacuees are moved one at a time in a fixed order (by location)
+    OrderedSequential,
+    /// Evacuees are moved one at a time, in a new random permutation every step
+    ShuffledSweep,
+}
+
+impl UpdateScheme {
+    /// Indices of `evacuees` in the order they are updated.
+    /// Every index appears once for the parallel scheme
+    pub fn order(&self, evacuees: &[EvacueeCell], rng: &mut impl RngCore) -> Vec<usize> {
+        let n = evacuees.len();
+        match self {
+            UpdateScheme::Parallel => (0..n).collect_vec(),
+            UpdateScheme::RandomSequential => (0..n).map(|_| rng.gen_range(0..n)).collect_vec(),
+            UpdateScheme::OrderedSequential => (0..n)
+                .sorted_by_key(|i| Loc(evacuees[*i].x, evacuees[*i].y))
+                .collect_vec(),
+            UpdateScheme::ShuffledSweep => {
+                let mut order = (0..n).collect_vec();
+                order.shuffle(rng);
+                order
+            }
+        }
+    }
+}
+
+/// Move the `evacuees` one at a time in `order` for a sub-step, every evacuee sees the moves made before it.
+/// Listed evacuees with a sub-step left spend it on moving to the cell `choose` picks given the occupied
+/// cells, evacuees missing from `order` lose the sub-step.
+/// Returns the indices of the evacuees that stepped into a cell `is_exit` accepts, in the order they got out.
+/// They keep their last cell and free it for the others
+pub fn sequential_moves(
+    evacuees: &mut [EvacueeCell],
+    order: &[usize],
+    mut choose: impl FnMut(&EvacueeCell, &HashSet<Loc>) -> Option<Loc>,
+    is_exit: impl Fn(&Loc) -> bool,
+) -> Vec<usize> {
+    let mut occupied: HashSet<Loc> = evacuees.iter().map(|e| Loc(e.x, e.y)).collect();
+    let drawn: HashSet<usize> = order.iter().copied().collect();
+    for (idx, e) in evacuees.iter_mut().enumerate() {
+        if !drawn.contains(&idx) && e.can_move() {
+            e.spend_substep();
+        }
+    }
+    let mut escaped = vec![];
+    for &idx in order {
+        let val = &mut evacuees[idx];
+        if escaped.contains(&idx) || !val.can_move() {
+            continue;
+        }
+        val.spend_substep();
+        let Some(opted_dist) = choose(val, &occupied) else {
+            continue;
+        };
+        occupied.remove(&Loc(val.x, val.y));
+        if is_exit(&opted_dist) {
+            escaped.push(idx);
+            continue;
+        }
+        occupied.insert(opted_dist);
+        val.x = opted_dist.0;
+        val.y = opted_dist.1;
+    }
+    escaped
+}
+
+/// How cells that are vacated during a tick are handled by the parallel update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum MoveResolution {
+    /// Occupied cells are never available, even if their occupant leaves during the same tick
+    #[default]
+    Blocking,
+    /// Occupied cells are available, moves are resolved in dependency order so that
+    /// an evacuee can only enter a cell whose occupant is leaving
+    Chain,
+}
+
+/// Pair the outcome of a game with the cells the evacuees started from.
+/// Losers keep their cell, the winner (if any) started from the only origin left unclaimed
+pub fn with_origins(
+    dist: Loc,
+    origins: &[Loc],
+    result: Vec<EvacueeCell>,
+) -> Vec<(Loc, EvacueeCell)> {
+    let stayed: HashSet<Loc> = result
+        .iter()
+        .map(|e| Loc(e.x, e.y))
+        .filter(|l| *l != dist)
+        .collect();
+    let winner_origin = origins.iter().find(|o| !stayed.contains(o)).copied();
+    result
+        .into_iter()
+        .map(|e| {
+            let loc = Loc(e.x, e.y);
+            if loc == dist {
+                (winner_origin.expect("Winner must have an origin"), e)
+            } else {
+                (loc, e)
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    InProgress,
+    Allowed,
+    Blocked,
+}
+
+/// Cancel every move into a cell whose occupant does not leave during the tick.
+/// Cycles of evacuees moving into each others cells are blocked as well
+/// # Arguments
+/// `placed` - Every evacuee that remains on the grid paired with the cell it started the tick from
+pub fn resolve_chain(placed: &mut [(Loc, EvacueeCell)]) {
+    let moves: HashMap<Loc, Loc> = placed
+        .iter()
+        .filter(|(origin, e)| *origin != Loc(e.x, e.y))
+        .map(|(origin, e)| (*origin, Loc(e.x, e.y)))
+        .collect();
+    let occupied: HashSet<Loc> = placed.iter().map(|(origin, _)| *origin).collect();
+    let mut visits: HashMap<Loc, Visit> = HashMap::with_capacity(moves.len());
+
+    fn visit(
+        origin: Loc,
+        moves: &HashMap<Loc, Loc>,
+        occupied: &HashSet<Loc>,
+        visits: &mut HashMap<Loc, Visit>,
+    ) -> bool {
+        match visits.get(&origin) {
+            Some(Visit::Allowed) => return true,
+            Some(Visit::Blocked) | Some(Visit::InProgress) => return false,
+            None => {}
+        }
+        visits.insert(origin, Visit::InProgress);
+        let target = moves[&origin];
+        let allowed = !occupied.contains(&target)
+            || (moves.contains_key(&target) && visit(target, moves, occupied, visits));
+        visits.insert(
+            origin,
+            if allowed {
+                Visit::Allowed
+            } else {
+                Visit::Blocked
+            },
+        );
+        allowed
+    }
+
+    for (origin, e) in placed.iter_mut() {
+        if moves.contains_key(origin) && !visit(*origin, &moves, &occupied, &mut visits) {
+            e.x = origin.0;
+            e.y = origin.1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    fn evac(x: i32, y: i32) -> EvacueeCell {
+        EvacueeCell {
+            x,
+            y,
+            ..Default::default()
+        }
+    }
+
+    /// Step towards `Loc(0, 0)` along the first axis if the cell is free
+    fn step_left(e: &EvacueeCell, occupied: &HashSet<Loc>) -> Option<Loc> {
+        let next = Loc(e.x - 1, e.y);
+        (!occupied.contains(&next)).then_some(next)
+    }
+
+    fn locs(placed: &[(Loc, EvacueeCell)]) -> Vec<Loc> {
+        placed.iter().map(|(_, e)| Loc(e.x, e.y)).collect()
+    }
+
+    fn population() -> Vec<EvacueeCell> {
+        vec![evac(3, 1), evac(0, 2), evac(2, 2), evac(0, 0), evac(1, 4)]
+    }
+
+    #[test]
+    fn parallel_order_is_identity() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let order = UpdateScheme::Parallel.order(&population(), &mut rng);
+        assert_eq!(order, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn ordered_sequential_is_sorted_by_location() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let order = UpdateScheme::OrderedSequential.order(&population(), &mut rng);
+        assert_eq!(order, vec![3, 1, 4, 2, 0]);
+    }
+
+    #[test]
+    fn shuffled_sweep_is_permutation() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let order = UpdateScheme::ShuffledSweep.order(&population(), &mut rng);
+        assert_eq!(
+            order.into_iter().sorted().collect_vec(),
+            vec![0, 1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn random_sequential_draws_within_range() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let order = UpdateScheme::RandomSequential.order(&population(), &mut rng);
+        assert_eq!(order.len(), 5);
+        assert!(order.into_iter().all(|i| i < 5));
+        assert!(UpdateScheme::RandomSequential
+            .order(&[], &mut rng)
+            .is_empty());
+    }
+
+    #[test]
+    fn sequential_moves_see_earlier_moves() {
+        let is_exit = |l: &Loc| *l == Loc(0, 0);
+        let mut evacuees = vec![evac(1, 0), evac(2, 0)];
+        evacuees.iter_mut().for_each(|e| e.move_budget = 1.);
+        // The first one gets out and the second one steps into the cell it left
+        let escaped = sequential_moves(&mut evacuees, &[0, 1], step_left, is_exit);
+        assert_eq!(escaped, vec![0]);
+        assert_eq!(Loc(evacuees[1].x, evacuees[1].y), Loc(1, 0));
+
+        let mut evacuees = vec![evac(1, 0), evac(2, 0)];
+        evacuees.iter_mut().for_each(|e| e.move_budget = 1.);
+        // The second one moves first and finds the cell taken
+        let escaped = sequential_moves(&mut evacuees, &[1, 0], step_left, is_exit);
+        assert_eq!(escaped, vec![0]);
+        assert_eq!(Loc(evacuees[1].x, evacuees[1].y), Loc(2, 0));
+        assert!(evacuees.iter().all(|e| !e.can_move()));
+    }
+
+    #[test]
+    fn sequential_moves_spend_the_substep() {
+        let mut evacuees = vec![evac(3, 0), evac(5, 0), evac(7, 0)];
+        evacuees[0].move_budget = 2.;
+        evacuees[1].move_budget = 1.;
+        // Drawn twice, the first one moves twice, the one left out loses its sub-step,
+        // the last one has no sub-step left
+        let escaped = sequential_moves(&mut evacuees, &[0, 0, 2], step_left, |_| false);
+        assert!(escaped.is_empty());
+        assert_eq!(evacuees.iter().map(|e| e.x).collect_vec(), vec![1, 5, 7]);
+        assert!(evacuees.iter().all(|e| !e.can_move()));
+        // Evacuees that cannot move still spend the sub-step
+        let mut evacuees = vec![evac(3, 0)];
+        evacuees[0].move_budget = 1.;
+        sequential_moves(&mut evacuees, &[0], |_, _| None, |_| false);
+        assert_eq!(evacuees[0].x, 3);
+        assert!(!evacuees[0].can_move());
+    }
+
+    #[test]
+    fn origins_of_single_winner() {
+        let placed = with_origins(Loc(1, 1), &[Loc(0, 1)], vec![evac(1, 1)]);
+        assert_eq!(placed[0].0, Loc(0, 1));
+    }
+
+    #[test]
+    fn origins_of_game() {
+        let placed = with_origins(
+            Loc(1, 1),
+            &[Loc(0, 1), Loc(1, 0), Loc(2, 1)],
+            vec![evac(1, 0), evac(1, 1), evac(2, 1)],
+        );
+        assert_eq!(placed[0].0, Loc(1, 0));
+        assert_eq!(placed[1].0, Loc(0, 1));
+        assert_eq!(placed[2].0, Loc(2, 1));
+    }
+
+    #[test]
+    fn origins_of_argument() {
+        let placed = with_origins(
+            Loc(1, 1),
+            &[Loc(0, 1), Loc(1, 0)],
+            vec![evac(0, 1), evac(1, 0)],
+        );
+        assert!(placed.iter().all(|(o, e)| *o == Loc(e.x, e.y)));
+    }
+
+    #[test]
+    fn queue_advances_in_one_tick() {
+        // Three evacuees in a corridor all stepping forward
+        let mut placed = vec![
+            (Loc(0, 2), evac(0, 3)),
+            (Loc(0, 1), evac(0, 2)),
+            (Loc(0, 0), evac(0, 1)),
+        ];
+        resolve_chain(&mut placed);
+        assert_eq!(locs(&placed), vec![Loc(0, 3), Loc(0, 2), Loc(0, 1)]);
+    }
+
+    #[test]
+    fn blocked_head_blocks_queue() {
+        let mut placed = vec![
+            (Loc(0, 2), evac(0, 2)),
+            (Loc(0, 1), evac(0, 2)),
+            (Loc(0, 0), evac(0, 1)),
+        ];
+        resolve_chain(&mut placed);
+        assert_eq!(locs(&placed), vec![Loc(0, 2), Loc(0, 1), Loc(0, 0)]);
+    }
+
+    #[test]
+    fn cycles_are_blocked() {
+        let mut placed = vec![(Loc(0, 0), evac(0, 1)), (Loc(0, 1), evac(0, 0))];
+        resolve_chain(&mut placed);
+        assert_eq!(locs(&placed), vec![Loc(0, 0), Loc(0, 1)]);
+    }
+
+    #[test]
+    fn free_cells_are_unaffected() {
+        let mut placed = vec![(Loc(0, 0), evac(0, 1)), (Loc(3, 3), evac(3, 3))];
+        resolve_chain(&mut placed);
+        assert_eq!(locs(&placed), vec![Loc(0, 1), Loc(3, 3)]);
+    }
+}
@@ -0,0 +1,157 @@
+use serde::Deserialize;
+
+use crate::model::misc::misc_func::Loc;
+
+/// Influence of the fire wardens on the evacuees around them
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct WardenPolicy {
+    /// Distance within which evacuees follow the directions of a warden
+    pub radius: f32,
+    /// Weight of every cell of extra distance to the exit chosen by the warden when picking a move
+    pub guidance: f32,
+    /// Chance of an adaptive evacuee close to a warden cooperating in a contest
+    #[serde(default)]
+    pub persuasion: f32,
+}
+
+/// Exit an evacuee is directed to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guide {
+    pub exit: Loc,
+    pub weight: f32,
+}
+
+impl Guide {
+    /// Extra distance to the exit of stepping into `loc`, relative to the closest of `neigh`
+    pub fn term(&self, loc: Loc, neigh: &[(Loc, f32)]) -> f32 {
+        let dist =
+            |Loc(x, y): Loc| (((x - self.exit.0).pow(2) + (y - self.exit.1).pow(2)) as f32).sqrt();
+        let closest = neigh
+            .iter()
+            .map(|(l, _)| dist(*l))
+            .fold(f32::INFINITY, f32::min);
+        -self.weight * (dist(loc) - closest)
+    }
+}
+
+/// Wardens in the building with the exit each of them sends the evacuees to
+#[derive(Debug, Clone)]
+pub struct Wardens {
+    pub policy: WardenPolicy,
+    /// Cell of every warden paired with its chosen exit
+    posts: Vec<(Loc, Loc)>,
+    /// Evacuees within the radius of a warden after the last update
+    guided: usize,
+}
+
+impl Wardens {
+    pub fn new(policy: WardenPolicy) -> Self {
+        Self {
+            policy,
+            posts: vec![],
+            guided: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.posts.clear();
+        self.guided = 0;
+    }
+
+    /// Exit a warden at `loc` directs the evacuees to. Wardens know every exit and the fire,
+    /// `exits` pairs the usable exits with their distance from the fire, `None` when nothing burns
+    pub fn choose_exit(loc: Loc, exits: &[(Loc, Option<f32>)]) -> Option<Loc> {
+        exits
+            .iter()
+            .map(|(e, fire)| {
+                let d = (((e.0 - loc.0).pow(2) + (e.1 - loc.1).pow(2)) as f32).sqrt();
+                // Without fire the closest exit is the best one
+                (*e, fire.map_or(d, |f| d - f))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(e, _)| e)
+    }
+
+    /// Refresh the cells and chosen exits of the wardens and count the `evacuees` they guide
+    pub fn update(&mut self, posts: Vec<(Loc, Loc)>, evacuees: &[Loc]) {
+        self.posts = posts;
+        self.guided = evacuees
+            .iter()
+            .filter(|l| self.guide(**l).is_some())
+            .count();
+    }
+
+    /// Directions of the closest warden within the radius of `loc`
+    pub fn guide(&self, loc: Loc) -> Option<Guide> {
+        let r_sq = self.policy.radius.powi(2);
+        self.posts
+            .iter()
+            .map(|(w, exit)| (((w.0 - loc.0).pow(2) + (w.1 - loc.1).pow(2)) as f32, *exit))
+            .filter(|(d, _)| *d <= r_sq)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, exit)| Guide {
+                exit,
+                weight: self.policy.guidance,
+            })
+    }
+
+    /// Number of wardens still in the building
+    pub fn count(&self) -> usize {
+        self.posts.len()
+    }
+
+    /// Number of evacuees within the radius of a warden
+    pub fn guided(&self) -> usize {
+        self.guided
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wardens() -> Wardens {
+        Wardens::new(WardenPolicy {
+            radius: 3.,
+            guidance: 2.,
+            persuasion: 0.5,
+        })
+    }
+
+    #[test]
+    fn test_choose_exit() {
+        let exits = [(Loc(0, 0), None), (Loc(10, 0), None)];
+        assert_eq!(Wardens::choose_exit(Loc(3, 3), &exits), Some(Loc(0, 0)));
+        // The closest exit is next to the fire
+        let exits = [(Loc(0, 0), Some(0.5)), (Loc(10, 0), Some(20.))];
+        assert_eq!(Wardens::choose_exit(Loc(3, 3), &exits), Some(Loc(10, 0)));
+        assert_eq!(Wardens::choose_exit(Loc(3, 3), &[]), None);
+    }
+
+    #[test]
+    fn test_guide() {
+        let mut w = wardens();
+        w.update(
+            vec![(Loc(5, 5), Loc(0, 0)), (Loc(7, 5), Loc(10, 0))],
+            &[Loc(5, 7), Loc(20, 20)],
+        );
+        assert_eq!(w.guide(Loc(5, 7)).map(|g| g.exit), Some(Loc(0, 0)));
+        assert_eq!(w.guide(Loc(8, 6)).map(|g| g.exit), Some(Loc(10, 0)));
+        assert_eq!(w.guide(Loc(20, 20)), None);
+        assert_eq!(w.count(), 2);
+        assert_eq!(w.guided(), 1);
+        w.reset();
+        assert_eq!(w.count(), 0);
+    }
+
+    #[test]
+    fn test_guide_term() {
+        let guide = Guide {
+            exit: Loc(0, 0),
+            weight: 2.,
+        };
+        let neigh = [(Loc(4, 5), 1.), (Loc(6, 5), 1.)];
+        assert_eq!(guide.term(Loc(4, 5), &neigh), 0.);
+        assert!(guide.term(Loc(6, 5), &neigh) < 0.);
+    }
+}
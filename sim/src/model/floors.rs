@@ -0,0 +1,545 @@
+use std::collections::{HashSet, VecDeque};
+
+use itertools::Itertools;
+use krabmaga::engine::fields::dense_number_grid_2d::DenseNumberGrid2D;
+use krabmaga::engine::location::Int2D;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use crate::model::evacuee_mod::evacuee_cell::EvacueeCell;
+use crate::model::evacuee_mod::neighbourhood::Neighbourhood;
+use crate::model::fire_mod::fire_cell::CellType;
+use crate::model::misc::misc_func::Loc;
+use crate::model::state::within_bounds;
+
+fn default_smoke_speed() -> f32 {
+    0.5
+}
+
+/// Stairwell linking every floor at the same cell
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Stair {
+    pub loc: (i32, i32),
+    /// Evacuees the stairwell holds at once
+    pub capacity: usize,
+    /// Floors descended per tick
+    pub speed: f32,
+}
+
+/// Floors above the ground floor and the way fire, smoke and evacuees move between them
+#[derive(Debug, Clone, Deserialize)]
+pub struct BuildingPolicy {
+    /// Number of floors above the ground floor
+    pub upper_floors: usize,
+    /// Evacuees starting on every upper floor
+    pub evac_per_floor: usize,
+    pub stairs: Vec<Stair>,
+    /// Chance of a burning cell setting the cell above on fire every tick
+    pub fire_up: f32,
+    /// Chance of a burning cell setting the cell below on fire every tick
+    #[serde(default)]
+    pub fire_down: f32,
+    /// Chance of smoke or fire filling the cell above with smoke every tick
+    pub smoke_up: f32,
+    /// Chance of smoke filling a neighbouring cell of the same floor every tick
+    #[serde(default)]
+    pub smoke_spread: f32,
+    /// Fraction of the speed kept walking through smoke
+    #[serde(default = "default_smoke_speed")]
+    pub smoke_speed: f32,
+}
+
+impl BuildingPolicy {
+    /// Check that every stair lies on its own cell within the `dim` of the floors
+    /// and moves evacuees down
+    pub fn validate(&self, (w, h): (u32, u32)) -> Result<(), String> {
+        for (i, s) in self.stairs.iter().enumerate() {
+            let ((x, y), speed) = (s.loc, s.speed);
+            let stair = format!("stair at ({x}, {y})");
+            if self.stairs[..i].iter().any(|other| other.loc == s.loc) {
+                return Err(format!("{stair} is declared more than once"));
+            }
+            if !within_bounds(x, w as i32) || !within_bounds(y, h as i32) {
+                return Err(format!("{stair} lies outside the {w}x{h} floors"));
+            }
+            if s.capacity == 0 {
+                return Err(format!("{stair} must hold at least one evacuee"));
+            }
+            if !speed.is_finite() || speed <= 0. {
+                return Err(format!("{stair} must have a speed above zero, got {speed}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Floor above the ground floor. Evacuees walk the shortest way around the fire to the closest stair
+/// without playing any game
+struct Floor {
+    fire: DenseNumberGrid2D<CellType>,
+    smoke: Vec<bool>,
+    evacuees: Vec<EvacueeCell>,
+}
+
+struct Stairwell {
+    stair: Stair,
+    /// Evacuees inside paired with the step they reach the ground floor
+    inside: VecDeque<(EvacueeCell, u64)>,
+}
+
+/// Upper floors of the building, the ground floor is the grid of the simulation
+pub struct Building {
+    pub policy: BuildingPolicy,
+    dim: (u32, u32),
+    /// First floor above the ground floor first
+    floors: Vec<Floor>,
+    stairwells: Vec<Stairwell>,
+    /// Evacuees on the upper floors at the start of the simulation
+    population: usize,
+}
+
+impl Building {
+    pub fn new(policy: BuildingPolicy, dim: (u32, u32)) -> Self {
+        let mut building = Self {
+            policy,
+            dim,
+            floors: vec![],
+            stairwells: vec![],
+            population: 0,
+        };
+        building.reset();
+        building
+    }
+
+    pub fn reset(&mut self) {
+        let (w, h) = self.dim;
+        self.floors = (0..self.policy.upper_floors)
+            .map(|_| Floor {
+                fire: DenseNumberGrid2D::new(w as i32, h as i32),
+                smoke: vec![false; (w * h) as usize],
+                evacuees: vec![],
+            })
+            .collect();
+        self.stairwells = self
+            .policy
+            .stairs
+            .iter()
+            .map(|s| Stairwell {
+                stair: *s,
+                inside: VecDeque::new(),
+            })
+            .collect();
+        self.population = 0;
+    }
+
+    fn index(&self, Loc(x, y): Loc) -> usize {
+        (x * self.dim.1 as i32 + y) as usize
+    }
+
+    fn in_bounds(&self, Loc(x, y): Loc) -> bool {
+        within_bounds(x, self.dim.0 as i32) && within_bounds(y, self.dim.1 as i32)
+    }
+
+    fn is_stair(&self, loc: Loc) -> bool {
+        self.policy
+            .stairs
+            .iter()
+            .any(|s| Loc(s.loc.0, s.loc.1) == loc)
+    }
+
+    /// Whether `loc` burns on the upper floor `floor`, counted from 0
+    fn burning(&self, floor: usize, loc: Loc) -> bool {
+        self.floors[floor].fire.get_value(&loc.into()) == Some(CellType::Fire)
+    }
+
+    /// Number of moves from every cell of the upper floor `floor` to the closest stair avoiding the fire,
+    /// `None` for the cells cut off from every stair
+    fn stair_distances(&self, floor: usize) -> Vec<Option<usize>> {
+        let mut dist = vec![None; (self.dim.0 * self.dim.1) as usize];
+        let mut queue = VecDeque::new();
+        for s in self.policy.stairs.iter() {
+            let loc = Loc(s.loc.0, s.loc.1);
+            if !self.burning(floor, loc) {
+                dist[self.index(loc)] = Some(0);
+                queue.push_back(loc);
+            }
+        }
+        while let Some(loc) = queue.pop_front() {
+            let d = dist[self.index(loc)].unwrap();
+            for (l, _) in Neighbourhood::Moore.neighbours(&loc) {
+                if self.in_bounds(l) && !self.burning(floor, l) && dist[self.index(l)].is_none() {
+                    dist[self.index(l)] = Some(d + 1);
+                    queue.push_back(l);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Place the evacuees of every upper floor on random free cells, ids start from `first_id`.
+    /// Returns the number of evacuees placed
+    pub fn populate(
+        &mut self,
+        first_id: usize,
+        rng: &mut dyn RngCore,
+        mut make: impl FnMut((i32, i32), &mut dyn RngCore) -> EvacueeCell,
+    ) -> usize {
+        let (w, h) = self.dim;
+        let stairs = self.policy.stairs.iter().map(|s| s.loc).unique().count();
+        let free = ((w * h) as usize).saturating_sub(stairs);
+        let per_floor = self.policy.evac_per_floor.min(free);
+        let mut id = first_id;
+        for f in 0..self.floors.len() {
+            let mut taken = vec![];
+            while taken.len() < per_floor {
+                let loc = Loc(rng.gen_range(0..w as i32), rng.gen_range(0..h as i32));
+                if self.is_stair(loc) || taken.contains(&loc) {
+                    continue;
+                }
+                taken.push(loc);
+                let mut e = make((loc.0, loc.1), &mut *rng);
+                e.id = id;
+                id += 1;
+                self.floors[f].evacuees.push(e);
+            }
+        }
+        self.population = id - first_id;
+        self.population
+    }
+
+    /// Evacuees on the upper floors at the start of the simulation
+    pub fn population(&self) -> usize {
+        self.population
+    }
+
+    /// Evacuees on the upper floors and inside the stairwells
+    pub fn evacuees(&self) -> impl Iterator<Item = &EvacueeCell> {
+        self.floors.iter().flat_map(|f| f.evacuees.iter()).chain(
+            self.stairwells
+                .iter()
+                .flat_map(|s| s.inside.iter().map(|(e, _)| e)),
+        )
+    }
+
+    /// Number of evacuees left on every upper floor, first floor first
+    pub fn remaining(&self) -> Vec<usize> {
+        self.floors.iter().map(|f| f.evacuees.len()).collect()
+    }
+
+    /// Number of evacuees inside the stairwells
+    pub fn in_stairs(&self) -> usize {
+        self.stairwells.iter().map(|s| s.inside.len()).sum()
+    }
+
+    /// Spread the fire and the smoke over the upper floors.
+    /// Fire spreads within a floor like on the ground floor with probability `spread` given the number of burning
+    /// neighbours, and between floors with the vertical probabilities of the policy.
+    /// `ground` - fire state of the ground floor. Returns the ground cells set on fire from above
+    pub fn fire_step(
+        &mut self,
+        ground: &[CellType],
+        spread: impl Fn(&CellType, usize) -> f32,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Loc> {
+        let cells = (0..self.dim.0 as i32)
+            .cartesian_product(0..self.dim.1 as i32)
+            .map(|(x, y)| Loc(x, y))
+            .collect_vec();
+        let burning_at = |b: &Self, floor: Option<usize>, loc: Loc| match floor {
+            None => ground[b.index(loc)] == CellType::Fire,
+            Some(f) => f < b.floors.len() && b.burning(f, loc),
+        };
+        let mut ignited_ground = vec![];
+        if !self.floors.is_empty() {
+            for loc in cells.iter() {
+                if ground[self.index(*loc)] == CellType::Empty
+                    && self.burning(0, *loc)
+                    && rng.gen::<f32>() < self.policy.fire_down
+                {
+                    ignited_ground.push(*loc);
+                }
+            }
+        }
+        let mut updates = vec![];
+        for f in 0..self.floors.len() {
+            let below = f.checked_sub(1);
+            let mut fire = Vec::with_capacity(cells.len());
+            let mut smoke = Vec::with_capacity(cells.len());
+            for loc in cells.iter() {
+                let idx = self.index(*loc);
+                let neighbours = Neighbourhood::Moore
+                    .neighbours(loc)
+                    .map(|(l, _)| l)
+                    .filter(|l| self.in_bounds(*l))
+                    .collect_vec();
+                let cell = if self.burning(f, *loc) {
+                    CellType::Fire
+                } else {
+                    let n = neighbours.iter().filter(|l| self.burning(f, **l)).count();
+                    let from_below = burning_at(self, below, *loc);
+                    let from_above = burning_at(self, Some(f + 1), *loc);
+                    if rng.gen::<f32>() < spread(&CellType::Empty, n)
+                        || (from_below && rng.gen::<f32>() < self.policy.fire_up)
+                        || (from_above && rng.gen::<f32>() < self.policy.fire_down)
+                    {
+                        CellType::Fire
+                    } else {
+                        CellType::Empty
+                    }
+                };
+                let smoky_below = match below {
+                    None => ground[idx] == CellType::Fire,
+                    Some(b) => self.floors[b].smoke[idx] || self.burning(b, *loc),
+                };
+                let smoky_around = neighbours
+                    .iter()
+                    .any(|l| self.floors[f].smoke[self.index(*l)]);
+                smoke.push(
+                    self.floors[f].smoke[idx]
+                        || cell == CellType::Fire
+                        || (smoky_below && rng.gen::<f32>() < self.policy.smoke_up)
+                        || (smoky_around && rng.gen::<f32>() < self.policy.smoke_spread),
+                );
+                fire.push(cell);
+            }
+            updates.push((fire, smoke));
+        }
+        for (floor, (fire, smoke)) in self.floors.iter_mut().zip(updates) {
+            for (loc, cell) in cells.iter().zip(fire) {
+                floor.fire.set_value_location(cell, &Int2D::from(*loc));
+            }
+            floor.fire.lazy_update();
+            floor.smoke = smoke;
+        }
+        ignited_ground
+    }
+
+    /// Move the evacuees of the upper floors one tick.
+    /// Evacuees on a stair enter its stairwell when there is room and reach the ground floor after
+    /// a number of ticks depending on the floor and the speed of the stair, the ones reaching a burning
+    /// stair cell of the ground floor die. The others walk the shortest way around the fire to the
+    /// closest stair, slowed down by smoke, and wait where the fire cut them off from every stair.
+    /// `free` - Whether a stair cell of the ground floor can take an arriving evacuee.
+    /// `burning` - Whether a stair cell of the ground floor is on fire.
+    /// Returns the evacuees arriving on the ground floor and the cells of the ones that burnt
+    pub fn evacuee_step(
+        &mut self,
+        step: u64,
+        free: impl Fn(&Loc) -> bool,
+        burning: impl Fn(&Loc) -> bool,
+        rng: &mut dyn RngCore,
+    ) -> (Vec<EvacueeCell>, Vec<Loc>) {
+        let mut arrivals = vec![];
+        let mut dead = vec![];
+        for well in self.stairwells.iter_mut() {
+            let loc = Loc(well.stair.loc.0, well.stair.loc.1);
+            while burning(&loc) && well.inside.front().is_some_and(|(_, t)| *t <= step) {
+                well.inside.pop_front();
+                dead.push(loc);
+            }
+            if well.inside.front().is_some_and(|(_, t)| *t <= step) && free(&loc) {
+                let (mut e, _) = well.inside.pop_front().unwrap();
+                e.x = loc.0;
+                e.y = loc.1;
+                arrivals.push(e);
+            }
+        }
+
+        for f in 0..self.floors.len() {
+            let mut evacuees = std::mem::take(&mut self.floors[f].evacuees);
+            evacuees.retain(|e| {
+                let burnt = self.burning(f, Loc(e.x, e.y));
+                if burnt {
+                    dead.push(Loc(e.x, e.y));
+                }
+                !burnt
+            });
+            let mut occupied: HashSet<Loc> = evacuees.iter().map(|e| Loc(e.x, e.y)).collect();
+            let dist = self.stair_distances(f);
+            let h = self.dim.1 as i32;
+            let dist = |&Loc(x, y): &Loc| dist[(x * h + y) as usize];
+            let mut remaining = vec![];
+            for mut e in evacuees {
+                let loc = Loc(e.x, e.y);
+                if let Some(well) = self
+                    .stairwells
+                    .iter_mut()
+                    .find(|w| Loc(w.stair.loc.0, w.stair.loc.1) == loc)
+                {
+                    if well.inside.len() < well.stair.capacity {
+                        let ticks = ((f + 1) as f32 / well.stair.speed.max(f32::EPSILON)).ceil();
+                        well.inside.push_back((e, step + ticks as u64));
+                        occupied.remove(&loc);
+                        continue;
+                    }
+                }
+                e.accumulate_budget();
+                while e.can_move() {
+                    e.spend_substep();
+                    let here = Loc(e.x, e.y);
+                    if self.floors[f].smoke[self.index(here)]
+                        && rng.gen::<f32>() >= self.policy.smoke_speed
+                    {
+                        continue;
+                    }
+                    let Some(own) = dist(&here) else {
+                        continue;
+                    };
+                    let next = Neighbourhood::Moore
+                        .neighbours(&here)
+                        .map(|(l, _)| l)
+                        .filter(|l| {
+                            self.in_bounds(*l)
+                                && dist(l).is_some_and(|d| d < own)
+                                && !occupied.contains(l)
+                        })
+                        .min_by_key(dist);
+                    if let Some(next) = next {
+                        occupied.remove(&here);
+                        occupied.insert(next);
+                        e.x = next.0;
+                        e.y = next.1;
+                    }
+                }
+                remaining.push(e);
+            }
+            self.floors[f].evacuees = remaining;
+        }
+        (arrivals, dead)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaChaRng;
+
+    fn policy(fire_up: f32) -> BuildingPolicy {
+        BuildingPolicy {
+            upper_floors: 2,
+            evac_per_floor: 1,
+            stairs: vec![Stair {
+                loc: (0, 0),
+                capacity: 1,
+                speed: 1.,
+            }],
+            fire_up,
+            fire_down: 0.,
+            smoke_up: 1.,
+            smoke_spread: 0.,
+            smoke_speed: 1.,
+        }
+    }
+
+    #[test]
+    fn test_walk_down_the_stairs() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let mut building = Building::new(policy(0.), (5, 5));
+        let placed = building.populate(10, &mut rng, |(x, y), _| EvacueeCell {
+            x,
+            y,
+            ..Default::default()
+        });
+        assert_eq!(placed, 2);
+        assert_eq!(building.population(), 2);
+        let mut arrived = vec![];
+        for step in 0..30 {
+            let (arrivals, dead) = building.evacuee_step(step, |_| true, |_| false, &mut rng);
+            assert!(dead.is_empty());
+            assert!(building.in_stairs() <= 1);
+            arrived.extend(arrivals);
+        }
+        assert_eq!(arrived.len(), 2);
+        assert!(arrived.iter().all(|e| (e.x, e.y) == (0, 0)));
+        assert_eq!(building.remaining(), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_fire_rises() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let mut building = Building::new(policy(1.), (3, 3));
+        let mut ground = vec![CellType::Empty; 9];
+        ground[4] = CellType::Fire;
+        let no_spread = |_: &CellType, _: usize| 0.;
+        building.fire_step(&ground, no_spread, &mut rng);
+        assert!(building.burning(0, Loc(1, 1)));
+        assert!(!building.burning(1, Loc(1, 1)));
+        assert!(!building.burning(0, Loc(0, 0)));
+        building.fire_step(&ground, no_spread, &mut rng);
+        assert!(building.burning(1, Loc(1, 1)));
+        assert!(building.floors[1].smoke[4]);
+    }
+
+    #[test]
+    fn test_walk_around_the_fire() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let mut building = Building::new(policy(0.), (5, 5));
+        // A wall of fire between the evacuee and the stair, open at the far end
+        for y in 0..4 {
+            building.floors[0]
+                .fire
+                .set_value_location(CellType::Fire, &Int2D { x: 2, y });
+        }
+        building.floors[0].fire.lazy_update();
+        building.floors[0].evacuees.push(EvacueeCell {
+            x: 4,
+            y: 0,
+            ..Default::default()
+        });
+        let dist = building.stair_distances(0);
+        assert_eq!(dist[building.index(Loc(4, 0))], Some(8));
+        let mut arrived = vec![];
+        for step in 0..30 {
+            let (arrivals, dead) = building.evacuee_step(step, |_| true, |_| false, &mut rng);
+            assert!(dead.is_empty());
+            arrived.extend(arrivals);
+        }
+        assert_eq!(arrived.len(), 1);
+    }
+
+    #[test]
+    fn test_burning_stair_bottom() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let mut building = Building::new(policy(0.), (5, 5));
+        building.floors[0].evacuees.push(EvacueeCell::default());
+        let (arrivals, dead) = building.evacuee_step(0, |_| true, |_| true, &mut rng);
+        assert!(arrivals.is_empty() && dead.is_empty());
+        assert_eq!(building.in_stairs(), 1);
+        let (arrivals, dead) = building.evacuee_step(1, |_| false, |_| true, &mut rng);
+        assert!(arrivals.is_empty());
+        assert_eq!(dead, vec![Loc(0, 0)]);
+        assert_eq!(building.in_stairs(), 0);
+    }
+
+    #[test]
+    fn test_validate() {
+        let p = policy(0.);
+        assert!(p.validate((5, 5)).is_ok());
+        assert!(p.validate((0, 5)).is_err());
+        let duplicate = BuildingPolicy {
+            stairs: vec![p.stairs[0], p.stairs[0]],
+            ..policy(0.)
+        };
+        assert!(duplicate.validate((5, 5)).is_err());
+        for stair in [
+            Stair {
+                loc: (5, 0),
+                ..p.stairs[0]
+            },
+            Stair {
+                capacity: 0,
+                ..p.stairs[0]
+            },
+            Stair {
+                speed: 0.,
+                ..p.stairs[0]
+            },
+        ] {
+            let p = BuildingPolicy {
+                stairs: vec![stair],
+                ..policy(0.)
+            };
+            assert!(p.validate((5, 5)).is_err());
+        }
+    }
+}
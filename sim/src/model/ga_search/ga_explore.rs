@@ -4,7 +4,11 @@ use rand::prelude::*;
 use rand::seq::SliceRandom;
 use std::sync::RwLock;
 
-use crate::model::{lerp::equations::Equation, search::InputSearch, state::CellGrid};
+use crate::model::{
+    lerp::equations::Equation,
+    search::{InputSearch, ASP_TYPES},
+    state::CellGrid,
+};
 use itertools::Itertools;
 use krabmaga::{engine::schedule::Schedule, *};
 
@@ -99,7 +103,9 @@ pub fn crossover(population: &mut Vec<String>) {
                             RNG.lock().unwrap().gen_range(pmin..=pmax).to_string()
                         }
                         Err(TypeEquation::Lerp) => RNG.lock().unwrap().gen_range(0..=3).to_string(),
-                        Err(TypeEquation::Asp) => RNG.lock().unwrap().gen_range(0..=1).to_string(),
+                        Err(TypeEquation::Asp) => {
+                            RNG.lock().unwrap().gen_range(0..ASP_TYPES).to_string()
+                        }
                     },
                 )
                 .collect::<Vec<_>>()
@@ -166,7 +172,9 @@ pub fn mutation(indiv: &mut String) {
                     .gen_range((c - ALPHA).max(small)..(c + ALPHA).min(big))
                     .to_string(),
                 Err(TypeEquation::Lerp) => RNG.lock().unwrap().gen_range(0..4u8).to_string(),
-                Err(TypeEquation::Asp) => RNG.lock().unwrap().gen_range(0..2u8).to_string(),
+                Err(TypeEquation::Asp) => {
+                    RNG.lock().unwrap().gen_range(0..ASP_TYPES).to_string()
+                }
             }
         })
         .collect_vec()
@@ -3,7 +3,10 @@ use lerp::Lerp;
 use rand_distr::Standard;
 use serde::Deserialize;
 
-use crate::model::lerp::equations::Equation;
+use crate::model::{
+    evacuee_mod::game::GameType,
+    lerp::{equations::Equation, table::Interpolation},
+};
 
 // use super::import::FixedOrRandom;
 
@@ -14,6 +17,9 @@ pub struct FireInput {
     pub aspiration: AspirationInput,
     pub ratio: RatioInput,
     pub reward_game: RewardGameInput,
+    /// Conflict game, prisoner's dilemma if missing
+    #[serde(default)]
+    pub game: GameType,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -30,21 +36,84 @@ pub enum MovementInput {
 pub enum AspirationInput {
     LogAspiration(Option<f32>),
     RootAspiration(Option<f32>),
+    LinearAspiration(Option<f32>),
+    /// Saturates at `factor` times `ASP_SCALE`, `rate` defaults to 0.01
+    ExpSaturationAspiration {
+        factor: Option<f32>,
+        rate: Option<f32>,
+    },
+    /// Logistic in the fire area, `rate` defaults to 0.01 and `midpoint` to 500 cells
+    LogisticAspiration {
+        factor: Option<f32>,
+        rate: Option<f32>,
+        midpoint: Option<f32>,
+    },
+    /// Grows with the logarithm of the elapsed steps
+    StepAspiration(Option<f32>),
+    /// Tabulated curve of the fire area
+    TableAspiration(TableInput),
+    /// Expression of the fire area `area` and the current `step`, e.g. `"0.3*ln(1+area)"`
+    ExpressionAspiration(String),
+    /// Every evacuee learns its aspiration from its own payoffs at `rate`
+    Habituation {
+        rate: Option<f32>,
+    },
+    /// Habituation blended with a global aspiration, `weight` is the share of the global term
+    BlendedHabituation {
+        rate: Option<f32>,
+        weight: Option<f32>,
+        global: Box<AspirationInput>,
+    },
 }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct RatioInput(pub LerpInput);
+#[serde(untagged)]
+pub enum RatioInput {
+    Lerp(LerpInput),
+    Table(TableInput),
+    Function(RatioFunction),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum RatioFunction {
+    RootDist(Option<f32>),
+    LogDist(Option<f32>),
+    IDdist(Option<f32>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RewardGameInput {
+    Lerp(LerpInput),
+    Table(TableInput),
+    Function(RewardFunction),
+}
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct RewardGameInput(pub LerpInput);
-// #[derive(Debug, Clone, Deserialize)]
-// pub enum RewardGameInput {
-//     InvLogRoot(Option<f32>),
-//     RewardRoot(Option<f32>),
-// }
+pub enum RewardFunction {
+    InvLogRoot(Option<f32>),
+    RewardRoot(Option<f32>),
+}
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct LerpInput {
     pub influence: Option<f32>,
-    pub equation: Equation,
+    pub equation: EquationInput,
+}
+
+/// Either one of the predefined equations or an expression of the distance `d`
+/// and the largest distance `dmax`, e.g. `"20*(1-d/dmax)^2"`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum EquationInput {
+    Named(Equation),
+    Expression(String),
+}
+
+/// Curve given by `(x, y)` control points, e.g. calibrated from empirical data
+#[derive(Debug, Clone, Deserialize)]
+pub struct TableInput {
+    pub points: Vec<(f32, f32)>,
+    #[serde(default)]
+    pub interpolation: Interpolation,
 }
@@ -1,6 +1,26 @@
 use serde::Deserialize;
 
-use crate::model::{evacuee_mod::evacuee_cell::EvacueeCell, state::SimType};
+use crate::model::{
+    evacuee_mod::{
+        assistance::AssistPolicy,
+        behaviour::BehaviourShare,
+        evacuee_cell::EvacueeCell,
+        firefighter::FirefighterPolicy,
+        game::{ArgumentPolicy, PushPolicy},
+        group::GroupPolicy,
+        learning::LearningModel,
+        mixed::StrategyRepresentation,
+        neighbourhood::Neighbourhood,
+        pressure::PressurePolicy,
+        queue::QueuePolicy,
+        reputation::ReciprocityRule,
+        speed::AgentClass,
+        update_scheme::{MoveResolution, UpdateScheme},
+        warden::WardenPolicy,
+    },
+    floors::BuildingPolicy,
+    state::SimType,
+};
 
 use super::fire_input::FireInput;
 
@@ -14,6 +34,38 @@ pub struct ImportImproved {
     pub escape: EscapeInput,
     pub death: DeathInput,
     pub static_input: StaticInput,
+    /// Movement neighbourhood of the evacuees, von Neumann if missing
+    #[serde(default)]
+    pub neighbourhood: Neighbourhood,
+    /// Speed-density relation, evacuees walk at their free speed if missing
+    pub density_speed: Option<DensitySpeedInput>,
+    /// Handling of cells vacated during a tick, blocking if missing
+    #[serde(default)]
+    pub move_resolution: MoveResolution,
+    /// Update discipline of the evacuees, parallel if missing
+    #[serde(default)]
+    pub update_scheme: UpdateScheme,
+    /// Resolution of arguments, the one of the game if missing
+    pub argument_policy: Option<ArgumentPolicy>,
+    /// Binary or continuous strategies, binary if missing
+    #[serde(default)]
+    pub strategy_representation: StrategyRepresentation,
+    /// Opponent memory, contests are anonymous if missing
+    pub reputation: Option<ReputationInput>,
+    /// Queueing at the exits, evacuees contest every cell if missing
+    pub queue: Option<QueuePolicy>,
+    /// Pushing of cooperative evacuees, nobody pushes if missing
+    pub push: Option<PushPolicy>,
+    /// Crowd pressure injuries, the crowd harms nobody if missing
+    pub pressure: Option<PressurePolicy>,
+    /// Helping of injured or slow evacuees, nobody helps if missing
+    pub assistance: Option<AssistPolicy>,
+    /// Guidance of the wardens, wardens behave like altruists if missing
+    pub wardens: Option<WardenPolicy>,
+    /// Firefighters entering through the exits, nobody fights the fire if missing
+    pub firefighters: Option<FirefighterPolicy>,
+    /// Floors above the ground floor, the building has a single floor if missing
+    pub floors: Option<BuildingPolicy>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,6 +77,16 @@ pub struct Setup {
     pub fire_spread: Option<f32>,
     pub lc: Option<f32>,
     pub ld: Option<f32>,
+    /// Speed classes of the evacuees with their proportions
+    pub agent_classes: Option<Vec<AgentClass>>,
+    /// Strategy learning rule, Bush-Mosteller if missing
+    pub learning: Option<LearningModel>,
+    /// Behavioural types of the evacuees with their proportions
+    pub behaviours: Option<Vec<BehaviourShare>>,
+    /// Distance to the closest exit within which free-riders compete
+    pub free_rider_radius: Option<f32>,
+    /// Groups evacuating together, everyone is alone if missing
+    pub groups: Option<GroupPolicy>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -37,6 +99,33 @@ pub enum DeathInput {
     AnnounceInput,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct DensitySpeedInput {
+    pub relation: DensityRelationInput,
+    /// Half width of the window the local density is measured on
+    pub radius: Option<i32>,
+    /// Area of a cell in m^2
+    pub cell_area: Option<f32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum DensityRelationInput {
+    Weidmann {
+        gamma: Option<f32>,
+        rho_max: Option<f32>,
+    },
+    Linear {
+        rho_max: Option<f32>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReputationInput {
+    pub rule: ReciprocityRule,
+    /// Number of encounters remembered by every evacuee
+    pub memory: Option<usize>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub enum StaticInput {
     ClosestToExit(Option<f32>),
@@ -5,15 +5,35 @@ use crate::model::{
     death::{Announcer, DeathHandler},
     escape::{EscapeHandler, EvacTime, TimeEscape},
     evacuee_mod::{
+        assistance::Assistance,
+        behaviour::DEFAULT_FREE_RIDER_RADIUS,
+        density::{DensitySpeed, LinearDensity, SpeedDensity, Weidmann, DEFAULT_CELL_AREA},
         fire_influence::{
             dynamic_influence::{ClosestDistance, DynamicInfluence},
             fire_influence::{FireInfluence, MAX_REWARD},
             frontier::{Frontier, FrontierStructure},
         },
+        firefighter::Firefighters,
+        group::Groups,
+        queue::ExitQueues,
+        reputation::{Reputation, DEFAULT_MEMORY},
         static_influence::{ExitInfluence, StaticInfluence},
-        strategies::aspiration_strategy::{AspirationStrategy, LogAspManip, RootAsp},
+        strategies::{
+            aspiration_strategy::{
+                AspirationStrategy, ExpSatAsp, ExpressionAsp, Habituation, LinearAsp, LogAspManip,
+                LogisticAsp, RootAsp, StepAsp,
+            },
+            ratio_strategy::{IDdist, LogDist, RatioStrategy, RootDist},
+            reward_strategy::{InverseLogRoot, RewardStrategy, RootReward},
+        },
+        warden::Wardens,
+    },
+    floors::Building,
+    lerp::{
+        equations::LerpStruct,
+        expression::{Expression, ExpressionCurve},
+        table::PiecewiseCurve,
     },
-    lerp::equations::LerpStruct,
     // file_handling::file_handler::FileHandler,
     misc::misc_func::Loc,
     state::{CellGrid, InitialConfig},
@@ -21,7 +41,10 @@ use crate::model::{
 
 use super::{
     fire_input::*,
-    import::{DeathInput, EscapeInput, ImportImproved, Setup, StaticInput},
+    import::{
+        DeathInput, DensityRelationInput, DensitySpeedInput, EscapeInput, ImportImproved,
+        ReputationInput, Setup, StaticInput,
+    },
 };
 
 pub trait ToSimulationStruct {
@@ -72,41 +95,163 @@ impl ToSimulationStruct for AspirationInput {
                 let e = e.unwrap_or_else(|| rng.gen());
                 Box::new(RootAsp(e))
             }
+            AspirationInput::LinearAspiration(e) => {
+                let e = e.unwrap_or_else(|| rng.gen());
+                Box::new(LinearAsp(e))
+            }
+            AspirationInput::ExpSaturationAspiration { factor, rate } => {
+                let def = ExpSatAsp::default();
+                Box::new(ExpSatAsp {
+                    factor: factor.unwrap_or_else(|| rng.gen()),
+                    rate: rate.unwrap_or(def.rate),
+                })
+            }
+            AspirationInput::LogisticAspiration {
+                factor,
+                rate,
+                midpoint,
+            } => {
+                let def = LogisticAsp::default();
+                Box::new(LogisticAsp {
+                    factor: factor.unwrap_or_else(|| rng.gen()),
+                    rate: rate.unwrap_or(def.rate),
+                    midpoint: midpoint.unwrap_or(def.midpoint),
+                })
+            }
+            AspirationInput::StepAspiration(e) => {
+                let e = e.unwrap_or_else(|| rng.gen());
+                Box::new(StepAsp(e))
+            }
+            AspirationInput::TableAspiration(t) => Box::new(t.to_struct(rng, &())),
+            AspirationInput::ExpressionAspiration(src) => Box::new(ExpressionAsp(
+                parse_expression(src, &ExpressionAsp::VARIABLES),
+            )),
+            AspirationInput::Habituation { rate } => Box::new(Habituation {
+                rate: rate.unwrap_or_else(|| rng.gen()),
+                weight: 0.,
+                global: None,
+            }),
+            AspirationInput::BlendedHabituation {
+                rate,
+                weight,
+                global,
+            } => Box::new(Habituation {
+                rate: rate.unwrap_or_else(|| rng.gen()),
+                weight: weight.unwrap_or_else(|| rng.gen()),
+                global: Some(global.to_struct(rng, &())),
+            }),
         }
     }
 }
 
+/// Abort the loading of a scenario with an invalid `section`
+fn check(section: &str, result: Result<(), String>) {
+    if let Err(e) = result {
+        panic!("Invalid {section}: {e}");
+    }
+}
+
+/// Error unless `value` is finite and above zero
+fn positive(name: &str, value: f32) -> Result<(), String> {
+    if value.is_finite() && value > 0. {
+        Ok(())
+    } else {
+        Err(format!("{name} must be above zero, got {value}"))
+    }
+}
+
+/// Parse an expression of the scenario, invalid expressions abort the loading
+fn parse_expression(src: &str, vars: &[&str]) -> Expression {
+    Expression::parse(src, vars)
+        .unwrap_or_else(|e| panic!("Invalid expression \"{src}\": {e}, variables are {vars:?}"))
+}
+
+/// Expressions give the curve directly, the influence only scales it
+fn expression_curve(src: &str, influence: Option<f32>, dmax: f32) -> ExpressionCurve {
+    ExpressionCurve {
+        expression: parse_expression(src, &ExpressionCurve::VARIABLES),
+        dmax,
+        factor: influence.unwrap_or(1.),
+    }
+}
+
+impl ToSimulationStruct for TableInput {
+    type T = PiecewiseCurve;
+
+    type P = ();
+
+    fn to_struct(&self, _rng: &mut dyn RngCore, _params: &Self::P) -> Self::T {
+        PiecewiseCurve::new(self.points.clone(), self.interpolation)
+            .expect("Table needs finite points with distinct x values")
+    }
+}
+
 impl ToSimulationStruct for RatioInput {
-    type T = LerpStruct;
+    type T = Box<dyn RatioStrategy + Send>;
 
     type P = f32;
 
     fn to_struct(&self, rng: &mut dyn RngCore, params: &Self::P) -> Self::T {
-        LerpStruct::new(
-            0.,
-            *params,
-            0.,
-            MAX_REWARD,
-            self.0.influence.unwrap_or_else(|| rng.gen()),
-            self.0.equation,
-        )
+        match self {
+            RatioInput::Lerp(LerpInput {
+                influence,
+                equation: EquationInput::Named(eq),
+            }) => Box::new(LerpStruct::new(
+                0.,
+                *params,
+                0.,
+                MAX_REWARD,
+                influence.unwrap_or_else(|| rng.gen()),
+                *eq,
+            )),
+            RatioInput::Lerp(LerpInput {
+                influence,
+                equation: EquationInput::Expression(src),
+            }) => Box::new(expression_curve(src, *influence, *params)),
+            RatioInput::Table(t) => Box::new(t.to_struct(rng, &())),
+            RatioInput::Function(RatioFunction::RootDist(e)) => {
+                Box::new(RootDist(e.unwrap_or_else(|| rng.gen())))
+            }
+            RatioInput::Function(RatioFunction::LogDist(e)) => {
+                Box::new(LogDist(e.unwrap_or_else(|| rng.gen())))
+            }
+            RatioInput::Function(RatioFunction::IDdist(e)) => {
+                Box::new(IDdist(e.unwrap_or_else(|| rng.gen())))
+            }
+        }
     }
 }
 
 impl ToSimulationStruct for RewardGameInput {
-    type T = LerpStruct;
+    type T = Box<dyn RewardStrategy + Send>;
 
     type P = f32;
 
     fn to_struct(&self, rng: &mut dyn RngCore, params: &Self::P) -> Self::T {
-        LerpStruct::new(
-            0.,
-            *params,
-            MAX_REWARD,
-            0.,
-            self.0.influence.unwrap_or_else(|| rng.gen()),
-            self.0.equation,
-        )
+        match self {
+            RewardGameInput::Lerp(LerpInput {
+                influence,
+                equation: EquationInput::Named(eq),
+            }) => Box::new(LerpStruct::new(
+                0.,
+                *params,
+                MAX_REWARD,
+                0.,
+                influence.unwrap_or_else(|| rng.gen()),
+                *eq,
+            )),
+            RewardGameInput::Lerp(LerpInput {
+                influence,
+                equation: EquationInput::Expression(src),
+            }) => Box::new(expression_curve(src, *influence, *params)),
+            RewardGameInput::Table(t) => Box::new(t.to_struct(rng, &())),
+            RewardGameInput::Function(RewardFunction::InvLogRoot(e)) => {
+                Box::new(InverseLogRoot(e.unwrap_or_else(|| rng.gen())))
+            }
+            RewardGameInput::Function(RewardFunction::RewardRoot(e)) => {
+                Box::new(RootReward(e.unwrap_or_else(|| rng.gen()), *params))
+            }
+        }
     }
 }
 
@@ -119,6 +264,7 @@ impl ToSimulationStruct for FireInput {
         let mx_dist = ((params.0 * params.0) as f32 + (params.1 * params.1) as f32).sqrt();
         FireInfluence {
             fire_area: 0,
+            step: 0,
             fire_state: self
                 .frontier
                 .clone()
@@ -128,6 +274,7 @@ impl ToSimulationStruct for FireInput {
             movement: self.movement.to_struct(rng, &()),
             ratio: self.ratio.to_struct(rng, &mx_dist),
             reward_game: self.reward_game.to_struct(rng, &mx_dist),
+            game: self.game,
         }
     }
 }
@@ -140,6 +287,17 @@ impl ToSimulationStruct for Setup {
     type P = (i32, i32); // w ,h
 
     fn to_struct(&self, _rng: &mut dyn RngCore, params: &Self::P) -> Self::T {
+        for class in self.agent_classes.iter().flatten() {
+            check("agent class", class.validate());
+        }
+        let models = self
+            .agent_classes
+            .iter()
+            .flatten()
+            .filter_map(|c| c.learning);
+        for model in self.learning.into_iter().chain(models) {
+            check("learning model", model.validate());
+        }
         InitialConfig {
             initial_grid: self.initial_fire,
             initial_evac_grid: self.initial_evac.clone(),
@@ -150,6 +308,10 @@ impl ToSimulationStruct for Setup {
                 .unwrap_or((params.0 as f32 * params.1 as f32 * 0.085) as usize),
             lc: self.lc,
             ld: self.ld,
+            agent_classes: self.agent_classes.clone().unwrap_or_default(),
+            learning: self.learning,
+            behaviours: self.behaviours.clone().unwrap_or_default(),
+            free_rider_radius: self.free_rider_radius.unwrap_or(DEFAULT_FREE_RIDER_RADIUS),
         }
     }
 }
@@ -199,6 +361,62 @@ impl ToSimulationStruct for StaticInput {
     }
 }
 
+impl ToSimulationStruct for DensityRelationInput {
+    type T = Box<dyn SpeedDensity + Send>;
+
+    type P = ();
+
+    fn to_struct(&self, _rng: &mut dyn RngCore, _params: &Self::P) -> Self::T {
+        match self {
+            DensityRelationInput::Weidmann { gamma, rho_max } => {
+                let default = Weidmann::default();
+                let rho_max = rho_max.unwrap_or(default.rho_max);
+                check("Weidmann relation", positive("rho_max", rho_max));
+                Box::new(Weidmann {
+                    gamma: gamma.unwrap_or(default.gamma),
+                    rho_max,
+                })
+            }
+            DensityRelationInput::Linear { rho_max } => {
+                let rho_max = rho_max.unwrap_or(LinearDensity::default().rho_max);
+                check("linear relation", positive("rho_max", rho_max));
+                Box::new(LinearDensity { rho_max })
+            }
+        }
+    }
+}
+
+impl ToSimulationStruct for DensitySpeedInput {
+    type T = DensitySpeed;
+
+    type P = ();
+
+    fn to_struct(&self, rng: &mut dyn RngCore, params: &Self::P) -> Self::T {
+        let radius = self.radius.unwrap_or(1);
+        let cell_area = self.cell_area.unwrap_or(DEFAULT_CELL_AREA);
+        check("density speed", positive("cell_area", cell_area));
+        assert!(
+            radius >= 0,
+            "Invalid density speed: radius must not be negative, got {radius}"
+        );
+        DensitySpeed {
+            relation: self.relation.to_struct(rng, params),
+            radius,
+            cell_area,
+        }
+    }
+}
+
+impl ToSimulationStruct for ReputationInput {
+    type T = Reputation;
+
+    type P = ();
+
+    fn to_struct(&self, _rng: &mut dyn RngCore, _params: &Self::P) -> Self::T {
+        Reputation::new(self.rule, self.memory.unwrap_or(DEFAULT_MEMORY))
+    }
+}
+
 //===================== Main =====================
 
 impl ToSimulationStruct for ImportImproved {
@@ -208,6 +426,18 @@ impl ToSimulationStruct for ImportImproved {
 
     fn to_struct(&self, rng: &mut dyn RngCore, _params: &Self::P) -> Self::T {
         let (w, h) = self.dim;
+        if let Some(push) = self.push {
+            check("push policy", push.validate());
+        }
+        if let Some(floors) = &self.floors {
+            check("floors", floors.validate(self.dim));
+        }
+        if let Some(assistance) = self.assistance {
+            check(
+                "assistance",
+                positive("speed_factor", assistance.speed_factor),
+            );
+        }
         CellGrid {
             step: 0,
             iteration: 0,
@@ -223,6 +453,21 @@ impl ToSimulationStruct for ImportImproved {
             static_influence: self
                 .static_input
                 .to_struct(rng, &Loc(w as i32 / 2, h as i32)),
+            neighbourhood: self.neighbourhood,
+            density_speed: self.density_speed.as_ref().map(|d| d.to_struct(rng, &())),
+            move_resolution: self.move_resolution,
+            update_scheme: self.update_scheme,
+            argument_policy: self.argument_policy,
+            strategy_representation: self.strategy_representation,
+            reputation: self.reputation.as_ref().map(|r| r.to_struct(rng, &())),
+            exit_queues: self.queue.map(ExitQueues::new),
+            push_policy: self.push,
+            pressure_policy: self.pressure,
+            assistance: self.assistance.map(Assistance::new),
+            groups: self.setup.groups.clone().map(Groups::new),
+            wardens: self.wardens.map(Wardens::new),
+            firefighters: self.firefighters.map(Firefighters::new),
+            building: self.floors.clone().map(|f| Building::new(f, self.dim)),
             ..Default::default()
         }
     }
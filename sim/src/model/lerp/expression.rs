@@ -0,0 +1,424 @@
+use thiserror::Error;
+
+/// Deepest evaluation stack an expression may need, also the deepest nesting of the source
+pub const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ExprError {
+    #[error("unexpected character '{1}' at {0}")]
+    UnexpectedChar(usize, char),
+    #[error("unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("invalid number at {0}")]
+    InvalidNumber(usize),
+    #[error("unknown variable '{0}'")]
+    UnknownVariable(String),
+    #[error("unknown function '{0}'")]
+    UnknownFunction(String),
+    #[error("expression is nested deeper than {MAX_DEPTH}")]
+    TooDeep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Num(f32),
+    Var(usize),
+    Neg,
+    Ln,
+    Exp,
+    Sqrt,
+    Abs,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Min,
+    Max,
+}
+
+impl Op {
+    /// Net change of the stack size
+    fn stack_effect(&self) -> isize {
+        match self {
+            Op::Num(_) | Op::Var(_) => 1,
+            Op::Neg | Op::Ln | Op::Exp | Op::Sqrt | Op::Abs => 0,
+            _ => -1,
+        }
+    }
+
+    fn function(name: &str) -> Option<(Self, usize)> {
+        match name {
+            "ln" => Some((Op::Ln, 1)),
+            "exp" => Some((Op::Exp, 1)),
+            "sqrt" => Some((Op::Sqrt, 1)),
+            "abs" => Some((Op::Abs, 1)),
+            "min" => Some((Op::Min, 2)),
+            "max" => Some((Op::Max, 2)),
+            _ => None,
+        }
+    }
+}
+
+/// Arithmetic expression over named variables, compiled to postfix order.
+/// Supports `+ - * / ^`, parentheses, unary minus and the functions `ln`, `exp`, `sqrt`, `abs`,
+/// `min` and `max`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    ops: Vec<Op>,
+}
+
+impl Expression {
+    /// Parse `src`, variables are referred to by their position in `vars` when evaluating
+    pub fn parse(src: &str, vars: &[&str]) -> Result<Self, ExprError> {
+        let mut parser = Parser {
+            src: src.as_bytes(),
+            pos: 0,
+            nesting: 0,
+            vars,
+            ops: Vec::new(),
+        };
+        parser.expr()?;
+        parser.skip_ws();
+        if let Some(c) = parser.peek() {
+            return Err(ExprError::UnexpectedChar(parser.pos, c as char));
+        }
+        let mut depth = 0;
+        for op in parser.ops.iter() {
+            depth += op.stack_effect();
+            if depth as usize > MAX_DEPTH {
+                return Err(ExprError::TooDeep);
+            }
+        }
+        Ok(Self { ops: parser.ops })
+    }
+
+    /// Evaluate with the values of the variables in the order given to `parse`
+    pub fn eval(&self, vars: &[f32]) -> f32 {
+        let mut stack = [0f32; MAX_DEPTH];
+        let mut top = 0;
+        for op in self.ops.iter() {
+            match *op {
+                Op::Num(v) => {
+                    stack[top] = v;
+                    top += 1;
+                }
+                Op::Var(i) => {
+                    stack[top] = vars[i];
+                    top += 1;
+                }
+                Op::Neg | Op::Ln | Op::Exp | Op::Sqrt | Op::Abs => {
+                    let a = stack[top - 1];
+                    stack[top - 1] = match *op {
+                        Op::Neg => -a,
+                        Op::Ln => a.ln(),
+                        Op::Exp => a.exp(),
+                        Op::Sqrt => a.sqrt(),
+                        _ => a.abs(),
+                    };
+                }
+                _ => {
+                    top -= 1;
+                    let (a, b) = (stack[top - 1], stack[top]);
+                    stack[top - 1] = match *op {
+                        Op::Add => a + b,
+                        Op::Sub => a - b,
+                        Op::Mul => a * b,
+                        Op::Div => a / b,
+                        Op::Pow => a.powf(b),
+                        Op::Min => a.min(b),
+                        _ => a.max(b),
+                    };
+                }
+            }
+        }
+        stack[0]
+    }
+}
+
+/// Recursive descent parser emitting the operations in postfix order
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    /// Depth of the current descent, bounded so deeply nested input cannot overflow the stack
+    nesting: usize,
+    vars: &'a [&'a str],
+    ops: Vec<Op>,
+}
+
+impl Parser<'_> {
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    /// Consume `c` if it is the next non whitespace character
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_ws();
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), ExprError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            match self.peek() {
+                Some(other) => Err(ExprError::UnexpectedChar(self.pos, other as char)),
+                None => Err(ExprError::UnexpectedEnd),
+            }
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<(), ExprError> {
+        self.term()?;
+        loop {
+            if self.eat(b'+') {
+                self.term()?;
+                self.ops.push(Op::Add);
+            } else if self.eat(b'-') {
+                self.term()?;
+                self.ops.push(Op::Sub);
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    // term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<(), ExprError> {
+        self.unary()?;
+        loop {
+            if self.eat(b'*') {
+                self.unary()?;
+                self.ops.push(Op::Mul);
+            } else if self.eat(b'/') {
+                self.unary()?;
+                self.ops.push(Op::Div);
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    // unary := '-' unary | power
+    fn unary(&mut self) -> Result<(), ExprError> {
+        // Every nested parenthesis, argument, exponent and minus goes through here
+        if self.nesting == MAX_DEPTH {
+            return Err(ExprError::TooDeep);
+        }
+        self.nesting += 1;
+        let result = if self.eat(b'-') {
+            self.unary().map(|_| self.ops.push(Op::Neg))
+        } else {
+            self.power()
+        };
+        self.nesting -= 1;
+        result
+    }
+
+    // power := atom ('^' unary)?, right associative
+    fn power(&mut self) -> Result<(), ExprError> {
+        self.atom()?;
+        if self.eat(b'^') {
+            self.unary()?;
+            self.ops.push(Op::Pow);
+        }
+        Ok(())
+    }
+
+    /// Whether the characters at the current position are an exponent such as `e-3`
+    fn at_exponent(&self) -> bool {
+        let digit_at = |i: usize| self.src.get(i).is_some_and(|c| c.is_ascii_digit());
+        match (self.peek(), self.src.get(self.pos + 1)) {
+            (Some(b'e' | b'E'), Some(b'+' | b'-')) => digit_at(self.pos + 2),
+            (Some(b'e' | b'E'), _) => digit_at(self.pos + 1),
+            _ => false,
+        }
+    }
+
+    // atom := number | variable | function '(' expr (',' expr)* ')' | '(' expr ')'
+    fn atom(&mut self) -> Result<(), ExprError> {
+        self.skip_ws();
+        let start = self.pos;
+        match self.peek() {
+            None => Err(ExprError::UnexpectedEnd),
+            Some(b'(') => {
+                self.pos += 1;
+                self.expr()?;
+                self.expect(b')')
+            }
+            Some(c) if c.is_ascii_digit() || c == b'.' => {
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == b'.') {
+                    self.pos += 1;
+                }
+                if self.at_exponent() {
+                    self.pos += 2;
+                    while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        self.pos += 1;
+                    }
+                }
+                let num = std::str::from_utf8(&self.src[start..self.pos])
+                    .ok()
+                    .and_then(|s| s.parse::<f32>().ok())
+                    .ok_or(ExprError::InvalidNumber(start))?;
+                self.ops.push(Op::Num(num));
+                Ok(())
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' => {
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_')
+                {
+                    self.pos += 1;
+                }
+                // only ascii characters were consumed
+                let name = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+                if self.eat(b'(') {
+                    let (op, arity) = Op::function(name)
+                        .ok_or_else(|| ExprError::UnknownFunction(name.to_owned()))?;
+                    for i in 0..arity {
+                        if i > 0 {
+                            self.expect(b',')?;
+                        }
+                        self.expr()?;
+                    }
+                    self.expect(b')')?;
+                    self.ops.push(op);
+                } else {
+                    let idx = self
+                        .vars
+                        .iter()
+                        .position(|v| *v == name)
+                        .ok_or_else(|| ExprError::UnknownVariable(name.to_owned()))?;
+                    self.ops.push(Op::Var(idx));
+                }
+                Ok(())
+            }
+            Some(c) => Err(ExprError::UnexpectedChar(start, c as char)),
+        }
+    }
+}
+
+/// Expression of the distance `d` and the largest distance `dmax`, scaled by `factor`
+#[derive(Debug, Clone)]
+pub struct ExpressionCurve {
+    pub expression: Expression,
+    pub dmax: f32,
+    pub factor: f32,
+}
+
+impl ExpressionCurve {
+    pub const VARIABLES: [&'static str; 2] = ["d", "dmax"];
+
+    pub fn eval(&self, d: f32) -> f32 {
+        self.expression.eval(&[d, self.dmax]) * self.factor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::misc::misc_func::relative_eq_close;
+    use approx::assert_relative_eq;
+    use proptest::prelude::*;
+
+    fn eval(src: &str, vars: &[&str], vals: &[f32]) -> f32 {
+        Expression::parse(src, vars).unwrap().eval(vals)
+    }
+
+    #[test]
+    fn precedence_test() {
+        assert_eq!(eval("1 + 2 * 3", &[], &[]), 7.);
+        assert_eq!(eval("(1 + 2) * 3", &[], &[]), 9.);
+        assert_eq!(eval("2 ^ 3 ^ 2", &[], &[]), 512.);
+        assert_eq!(eval("-2 ^ 2", &[], &[]), -4.);
+        assert_eq!(eval("2 ^ -1", &[], &[]), 0.5);
+        assert_eq!(eval("8 / 4 / 2", &[], &[]), 1.);
+        assert_eq!(eval("max(1, min(5, 3))", &[], &[]), 3.);
+    }
+
+    #[test]
+    fn exponent_test() {
+        assert_relative_eq!(eval("1e-3", &[], &[]), 1e-3);
+        assert_relative_eq!(eval("2.5E+2 * d", &["d"], &[2.]), 500.);
+        assert_relative_eq!(eval("3e2", &[], &[]), 300.);
+        assert_eq!(
+            Expression::parse("2e", &["e"]),
+            Err(ExprError::UnexpectedChar(1, 'e'))
+        );
+    }
+
+    #[test]
+    fn examples_test() {
+        assert_relative_eq!(
+            eval("0.3*ln(1+area)", &["area", "step"], &[9., 0.]),
+            0.3 * 10f32.ln()
+        );
+        assert_relative_eq!(
+            eval("20*(1-d/dmax)^2", &ExpressionCurve::VARIABLES, &[5., 10.]),
+            5.
+        );
+    }
+
+    #[test]
+    fn invalid_test() {
+        let vars = ["d"];
+        assert_eq!(
+            Expression::parse("x + 1", &vars),
+            Err(ExprError::UnknownVariable("x".to_owned()))
+        );
+        assert_eq!(
+            Expression::parse("cos(d)", &vars),
+            Err(ExprError::UnknownFunction("cos".to_owned()))
+        );
+        assert_eq!(
+            Expression::parse("(d + 1", &vars),
+            Err(ExprError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Expression::parse("d d", &vars),
+            Err(ExprError::UnexpectedChar(2, 'd'))
+        );
+        assert_eq!(
+            Expression::parse("1..2", &vars),
+            Err(ExprError::InvalidNumber(0))
+        );
+        assert_eq!(Expression::parse("", &vars), Err(ExprError::UnexpectedEnd));
+        assert_eq!(
+            Expression::parse("max(d)", &vars),
+            Err(ExprError::UnexpectedChar(5, ')'))
+        );
+        let deep = format!(
+            "{}1{}",
+            "(1+".repeat(MAX_DEPTH + 1),
+            ")".repeat(MAX_DEPTH + 1)
+        );
+        assert_eq!(Expression::parse(&deep, &vars), Err(ExprError::TooDeep));
+        // Rejected while parsing instead of overflowing the stack
+        for src in [
+            "(".repeat(100_000),
+            "-".repeat(100_000),
+            "2^".repeat(100_000),
+        ] {
+            assert_eq!(Expression::parse(&src, &vars), Err(ExprError::TooDeep));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_linear_expression(a in -10f32..10., b in -10f32..10., x in -10f32..10.) {
+            let expr = Expression::parse(&format!("{a} * x + ({b})"), &["x"]).unwrap();
+            prop_assert!(relative_eq_close(expr.eval(&[x]), a * x + b));
+        }
+    }
+}
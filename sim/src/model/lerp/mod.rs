@@ -1 +1,3 @@
 pub mod equations;
+pub mod expression;
+pub mod table;
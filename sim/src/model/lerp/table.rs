@@ -0,0 +1,158 @@
+use serde::Deserialize;
+
+/// Interpolation between the control points of a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// Fritsch-Carlson cubic Hermite, does not overshoot monotone data
+    MonotoneCubic,
+}
+
+/// Curve defined by `(x, y)` control points, constant outside of the first and last point
+#[derive(Debug, Clone)]
+pub struct PiecewiseCurve {
+    xs: Vec<f32>,
+    ys: Vec<f32>,
+    /// Tangent at every control point, only used by the cubic interpolation
+    tangents: Vec<f32>,
+    interpolation: Interpolation,
+}
+
+impl PiecewiseCurve {
+    /// Returns `None` if there are no points, a value is not finite or two points share the same x
+    pub fn new(mut points: Vec<(f32, f32)>, interpolation: Interpolation) -> Option<Self> {
+        if points.is_empty() || points.iter().any(|(x, y)| !x.is_finite() || !y.is_finite()) {
+            return None;
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if points.windows(2).any(|w| w[0].0 == w[1].0) {
+            return None;
+        }
+        let (xs, ys): (Vec<_>, Vec<_>) = points.into_iter().unzip();
+        let tangents = monotone_tangents(&xs, &ys);
+        Some(Self {
+            xs,
+            ys,
+            tangents,
+            interpolation,
+        })
+    }
+
+    /// Value of the curve at `input`, non-finite inputs give the value of the first point
+    pub fn eval(&self, input: f32) -> f32 {
+        let last = self.xs.len() - 1;
+        if !input.is_finite() || input <= self.xs[0] {
+            return self.ys[0];
+        }
+        if input >= self.xs[last] {
+            return self.ys[last];
+        }
+        let i = self.xs.partition_point(|x| *x <= input) - 1;
+        let h = self.xs[i + 1] - self.xs[i];
+        let t = (input - self.xs[i]) / h;
+        let (y0, y1) = (self.ys[i], self.ys[i + 1]);
+        match self.interpolation {
+            Interpolation::Linear => y0 + (y1 - y0) * t,
+            Interpolation::MonotoneCubic => {
+                let (t2, t3) = (t * t, t * t * t);
+                (2. * t3 - 3. * t2 + 1.) * y0
+                    + (t3 - 2. * t2 + t) * h * self.tangents[i]
+                    + (-2. * t3 + 3. * t2) * y1
+                    + (t3 - t2) * h * self.tangents[i + 1]
+            }
+        }
+    }
+}
+
+/// Fritsch-Carlson tangents of the sorted control points
+fn monotone_tangents(xs: &[f32], ys: &[f32]) -> Vec<f32> {
+    let n = xs.len();
+    if n < 2 {
+        return vec![0.; n];
+    }
+    let secants = (0..n - 1)
+        .map(|k| (ys[k + 1] - ys[k]) / (xs[k + 1] - xs[k]))
+        .collect::<Vec<_>>();
+    let mut tangents = Vec::with_capacity(n);
+    tangents.push(secants[0]);
+    for k in 1..n - 1 {
+        let (d0, d1) = (secants[k - 1], secants[k]);
+        tangents.push(if d0 * d1 <= 0. { 0. } else { (d0 + d1) / 2. });
+    }
+    tangents.push(secants[n - 2]);
+    for (k, d) in secants.iter().enumerate() {
+        if *d == 0. {
+            tangents[k] = 0.;
+            tangents[k + 1] = 0.;
+            continue;
+        }
+        let (a, b) = (tangents[k] / d, tangents[k + 1] / d);
+        let s = a * a + b * b;
+        if s > 9. {
+            let t = 3. / s.sqrt();
+            tangents[k] = t * a * d;
+            tangents[k + 1] = t * b * d;
+        }
+    }
+    tangents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::misc::misc_func::relative_eq_close;
+    use proptest::prelude::*;
+
+    fn table(ys: &[f32], interpolation: Interpolation) -> PiecewiseCurve {
+        let points = ys.iter().enumerate().map(|(i, y)| (i as f32, *y)).collect();
+        PiecewiseCurve::new(points, interpolation).unwrap()
+    }
+
+    #[test]
+    fn invalid_tables() {
+        assert!(PiecewiseCurve::new(vec![], Interpolation::Linear).is_none());
+        assert!(PiecewiseCurve::new(vec![(1., 0.), (1., 2.)], Interpolation::Linear).is_none());
+        assert!(PiecewiseCurve::new(vec![(f32::NAN, 0.)], Interpolation::Linear).is_none());
+    }
+
+    #[test]
+    fn non_finite_input() {
+        let curve = table(&[1., 2., 3.], Interpolation::MonotoneCubic);
+        for input in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(curve.eval(input), 1.);
+        }
+    }
+
+    #[test]
+    fn linear_test() {
+        let curve = PiecewiseCurve::new(vec![(10., 0.), (0., 2.), (5., 1.)], Interpolation::Linear)
+            .unwrap();
+        assert_eq!(curve.eval(2.5), 1.5);
+        assert_eq!(curve.eval(7.5), 0.5);
+        // constant outside of the table
+        assert_eq!(curve.eval(-3.), 2.);
+        assert_eq!(curve.eval(30.), 0.);
+        assert_eq!(table(&[4.], Interpolation::MonotoneCubic).eval(1.), 4.);
+    }
+
+    proptest! {
+        #[test]
+        fn test_passes_through_points(ys in proptest::collection::vec(-20f32..20., 2..10), cubic in any::<bool>()) {
+            let interpolation = if cubic { Interpolation::MonotoneCubic } else { Interpolation::Linear };
+            let curve = table(&ys, interpolation);
+            for (i, y) in ys.iter().enumerate() {
+                prop_assert!(relative_eq_close(curve.eval(i as f32), *y));
+            }
+        }
+
+        #[test]
+        fn test_cubic_keeps_monotony(mut ys in proptest::collection::vec(-20f32..20., 2..10), x in 0f32..9.) {
+            ys.sort_by(|a, b| a.total_cmp(b));
+            let curve = table(&ys, Interpolation::MonotoneCubic);
+            let (lo, hi) = (curve.eval(x), curve.eval(x + 0.05));
+            prop_assert!(hi >= lo - 1e-4);
+            prop_assert!(lo >= ys[0] - 1e-4 && lo <= ys[ys.len() - 1] + 1e-4);
+        }
+    }
+}
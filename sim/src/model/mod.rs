@@ -5,6 +5,7 @@ pub mod escape;
 pub mod evacuee_mod;
 pub mod file_handling;
 pub mod fire_mod;
+pub mod floors;
 pub mod ga_search;
 pub mod input_handling;
 pub mod lerp;
@@ -4,24 +4,57 @@ use rand::RngCore;
 use crate::model::ga_search::ga_explore::DNA_SIZE;
 
 use super::evacuee_mod::strategies::aspiration_strategy::AspirationStrategy;
+use super::evacuee_mod::strategies::aspiration_strategy::ExpSatAsp;
+use super::evacuee_mod::strategies::aspiration_strategy::LinearAsp;
 use super::evacuee_mod::strategies::aspiration_strategy::LogAspManip;
+use super::evacuee_mod::strategies::aspiration_strategy::LogisticAsp;
 use super::evacuee_mod::strategies::aspiration_strategy::RootAsp;
+use super::evacuee_mod::strategies::aspiration_strategy::StepAsp;
+use super::evacuee_mod::strategies::ratio_strategy::RatioStrategy;
+use super::evacuee_mod::strategies::reward_strategy::RewardStrategy;
 use super::lerp::equations::Equation;
 use super::lerp::equations::LerpStruct;
 
+/// Number of aspiration functions selectable by gene 2
+pub const ASP_TYPES: u8 = 6;
+
 #[derive(Debug, Default)]
 pub struct OutputVariables {
     pub per_case_ratio_1: u64, // a:b:c take a
     pub per_case_ratio_2: u64, // a:b:c take b
     pub per_case_ratio_3: u64, // a:b:c take c
+    /// Arguments where nobody got the cell
+    pub arguments_blocked: u64,
+    /// Arguments won by one of the evacuees
+    pub arguments_won: u64,
+    /// Evacuees injured during arguments
+    pub argument_injuries: u64,
+    /// Cooperative evacuees pushed out of their cell
+    pub pushes: u64,
+    /// Evacuees injured while being pushed
+    pub push_injuries: u64,
+    /// Evacuees crushed to death
+    pub crush_deaths: u64,
+    /// Evacuees crushed to death close to an exit
+    pub exit_crush: u64,
+    /// Evacuees injured by the pressure of the crowd
+    pub crush_injuries: u64,
+    /// Contests where a warden talked an evacuee into cooperating
+    pub persuaded: u64,
+}
+
+impl OutputVariables {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
 }
 
 pub struct InputSearch {
     pub lc: f32,
     pub ld: f32,
     pub asp_infl: Box<dyn AspirationStrategy + Send>,
-    pub rat_infl: LerpStruct,
-    pub reward_infl: LerpStruct,
+    pub rat_infl: Box<dyn RatioStrategy + Send>,
+    pub reward_infl: Box<dyn RewardStrategy + Send>,
     pub dynamc_infl: f32,
     pub static_infl: f32,
 }
@@ -41,8 +74,8 @@ impl InputSearch {
             lc: v[0] as f32,
             ld: v[1] as f32,
             asp_infl: gen_sel_asp((v[2], v[3])),
-            rat_infl: gen_self_ratio((v[4], v[5], v[10]), dist).expect("Invalid type"),
-            reward_infl: gen_self_rew((v[6], v[7], v[10]), dist).expect("Invalid type"),
+            rat_infl: Box::new(gen_self_ratio((v[4], v[5], v[10]), dist).expect("Invalid type")),
+            reward_infl: Box::new(gen_self_rew((v[6], v[7], v[10]), dist).expect("Invalid type")),
             static_infl: v[8] as f32,
             dynamc_infl: v[9] as f32,
         }
@@ -55,7 +88,7 @@ impl InputSearch {
         let reward_infl = rng.gen_range(0. ..1.0_f64);
         let static_infl = rng.gen_range(0. ..3.0_f64);
         let dynamc_infl = rng.gen_range(0. ..3.0_f64);
-        let asp_eq = rng.gen_range(0..2u8) as f64;
+        let asp_eq = rng.gen_range(0..ASP_TYPES) as f64;
         let reward_eq = rng.gen_range(0..4u8) as f64;
         let rat_eq = rng.gen_range(0..4u8) as f64;
         let reward_limit = rng.gen_range(25. ..=100f64);
@@ -80,6 +113,16 @@ fn gen_sel_asp((typ, prob): (f64, f64)) -> Box<dyn AspirationStrategy + Send> {
     match typ as u8 {
         0 => Box::new(LogAspManip(prob)),
         1 => Box::new(RootAsp(prob)),
+        2 => Box::new(LinearAsp(prob)),
+        3 => Box::new(ExpSatAsp {
+            factor: prob,
+            ..Default::default()
+        }),
+        4 => Box::new(LogisticAsp {
+            factor: prob,
+            ..Default::default()
+        }),
+        5 => Box::new(StepAsp(prob)),
         _ => unreachable!(),
     }
 }
@@ -107,4 +150,21 @@ mod tests {
         let arr = InputSearch::generate_set_of_parameters(&mut rng);
         assert_eq!(arr.len(), DNA_SIZE);
     }
+
+    #[test]
+    fn every_aspiration_type_is_selectable() {
+        // Aspiration of every type at a few fire areas and steps
+        let samples = (0..ASP_TYPES)
+            .map(|typ| {
+                let asp = gen_sel_asp((typ as f64, 0.5));
+                [(10, 5), (100, 50), (1000, 500)]
+                    .map(|(cells, step)| asp.calculate_asp(cells, step))
+            })
+            .collect::<Vec<_>>();
+        for (i, a) in samples.iter().enumerate() {
+            for b in samples[i + 1..].iter() {
+                assert!(a.iter().zip(b).any(|(x, y)| (x - y).abs() > 1e-3));
+            }
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::model::fire_mod::fire_cell::*;
-use crate::model::misc::misc_func::round;
+use crate::model::misc::misc_func::{distsq, round};
 use itertools::Itertools;
 use krabmaga::engine::fields::field::Field;
 use krabmaga::engine::state::State;
@@ -15,15 +15,35 @@ use serde::Deserialize;
 use std::cell::RefCell;
 use std::collections::HashSet;
 
-use super::death::{Announcer, DeathHandler};
+use super::death::{Announcer, DeathCause, DeathHandler, CAUSES};
 use super::escape::{EscapeHandler, EvacTime, TimeEscape};
+use super::evacuee_mod::assistance::Assistance;
+use super::evacuee_mod::behaviour::{sample_behaviour, Behaviour, BehaviourShare, BEHAVIOURS};
+use super::evacuee_mod::density::DensitySpeed;
 use super::evacuee_mod::evacuee::EvacueeAgent;
 use super::evacuee_mod::evacuee_cell::EvacueeCell;
 use super::evacuee_mod::fire_influence::dynamic_influence::ClosestDistance;
 use super::evacuee_mod::fire_influence::fire_influence::FireInfluence;
 use super::evacuee_mod::fire_influence::frontier::Frontier;
+use super::evacuee_mod::firefighter::Firefighters;
+use super::evacuee_mod::game::{push_destination, push_target, ArgumentPolicy, PushPolicy};
+use super::evacuee_mod::group::{Groups, Member};
+use super::evacuee_mod::learning::{LearningModel, SharedLearning};
+use super::evacuee_mod::mixed::{
+    expected_payoff, level_histogram, sample_action, StrategyRepresentation, LEVEL_BINS,
+};
+use super::evacuee_mod::neighbourhood::Neighbourhood;
+use super::evacuee_mod::pressure::{CrushOutcome, Presser, PressurePolicy};
+use super::evacuee_mod::queue::ExitQueues;
+use super::evacuee_mod::reputation::Reputation;
+use super::evacuee_mod::speed::{run_substeps, sample_class, AgentClass};
 use super::evacuee_mod::static_influence::{ExitInfluence, StaticInfluence};
-use super::evacuee_mod::strategy::rules;
+use super::evacuee_mod::strategy::{rules, s_x, Outcome, RuleCase, Strategy, RSTP};
+use super::evacuee_mod::update_scheme::{
+    resolve_chain, sequential_moves, with_origins, MoveResolution, UpdateScheme,
+};
+use super::evacuee_mod::warden::Wardens;
+use super::floors::Building;
 // use super::file_handling::file_handler::FileHandler;
 use super::misc::misc_func::Loc;
 use super::search::*;
@@ -34,6 +54,8 @@ use crate::model::fire_mod::fire_spread::FireRules;
 pub const DEFAULT_HEIGHT: u32 = 51;
 /// Default Width of the room. Plus 1 for wall
 pub const DEFAULT_WIDTH: u32 = 51;
+/// Attempts at placing a group member around the first one before placing it anywhere
+const MAX_GROUP_PLACEMENT: usize = 100;
 
 /// Initial Configuration of the simulation struct. Will be used to import the map or any other additional information
 /// such as parameters
@@ -46,6 +68,53 @@ pub struct InitialConfig {
     pub lc: Option<f32>,
     pub ld: Option<f32>,
     pub fire_spread: Option<f32>,
+    /// Speed classes of the generated evacuees, everyone walks at 1 cell per tick if empty
+    pub agent_classes: Vec<AgentClass>,
+    /// Learning rule of classes without their own, Bush-Mosteller if missing
+    pub learning: Option<LearningModel>,
+    /// Behavioural types of the generated evacuees, everyone is adaptive if empty
+    pub behaviours: Vec<BehaviourShare>,
+    /// Distance to the closest exit within which free-riders compete
+    pub free_rider_radius: f32,
+}
+
+impl InitialConfig {
+    /// Learning rule of every agent class, `lc` and `ld` are the Bush-Mosteller learning rates
+    pub fn learning_rules(&self, lc: f32, ld: f32) -> Vec<SharedLearning> {
+        let default = self.learning.unwrap_or_default();
+        if self.agent_classes.is_empty() {
+            return vec![default.rule(lc, ld)];
+        }
+        self.agent_classes
+            .iter()
+            .map(|c| c.learning.unwrap_or(default).rule(lc, ld))
+            .collect()
+    }
+
+    /// Evacuee at `loc` with a random strategy, cooperation probability, class and behaviour
+    pub fn random_evacuee(&self, loc: (i32, i32), rng: &mut dyn RngCore) -> EvacueeCell {
+        let strat = rng.gen();
+        let prob = rng.gen();
+        let class = sample_class(&self.agent_classes, rng);
+        let (speed, strength) = self
+            .agent_classes
+            .get(class)
+            .map_or((1., 1.), |c| (c.profile.speed(), c.strength));
+        let behaviour = sample_behaviour(&self.behaviours, rng);
+        EvacueeCell {
+            strategy: strat,
+            x: loc.0,
+            y: loc.1,
+            pr_c: prob,
+            pr_d: prob,
+            coop_level: prob,
+            class,
+            speed,
+            strength,
+            behaviour,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Deserialize)]
@@ -73,6 +142,38 @@ pub struct CellGrid {
     pub escape_handler: Box<dyn EscapeHandler<EvacTime> + Send>,
     pub death_handler: Box<dyn DeathHandler + Send>,
     pub static_influence: Box<dyn StaticInfluence + Send>,
+    /// Cells an evacuee can move to in a single step
+    pub neighbourhood: Neighbourhood,
+    /// Slow down evacuees in dense crowds, constant speed if missing
+    pub density_speed: Option<DensitySpeed>,
+    /// Whether evacuees can move into cells vacated during the same tick
+    pub move_resolution: MoveResolution,
+    /// Order in which the evacuees are moved
+    pub update_scheme: UpdateScheme,
+    /// Resolution of arguments, the one of the game if missing
+    pub argument_policy: Option<ArgumentPolicy>,
+    /// Binary or continuous strategies
+    pub strategy_representation: StrategyRepresentation,
+    /// Number of evacuees of every behavioural type at the start of the simulation
+    pub population_by_behaviour: [usize; BEHAVIOURS.len()],
+    /// Memory of past opponents, contests are anonymous if missing
+    pub reputation: Option<Reputation>,
+    /// FIFO queues at the exits, evacuees contest every cell if missing
+    pub exit_queues: Option<ExitQueues>,
+    /// Pushing of cooperative evacuees, nobody pushes if missing
+    pub push_policy: Option<PushPolicy>,
+    /// Crowd pressure injuries, the crowd harms nobody if missing
+    pub pressure_policy: Option<PressurePolicy>,
+    /// Cooperative evacuees helping injured or slow neighbours, nobody helps if missing
+    pub assistance: Option<Assistance>,
+    /// Groups evacuating together, everyone is alone if missing
+    pub groups: Option<Groups>,
+    /// Guidance of the wardens, wardens behave like altruists if missing
+    pub wardens: Option<Wardens>,
+    /// Firefighters entering through the exits, nobody fights the fire if missing
+    pub firefighters: Option<Firefighters>,
+    /// Floors above the ground floor, the building has a single floor if missing
+    pub building: Option<Building>,
     // pub inp_handlers : Handlers,
     pub output_vars: OutputVariables,
 }
@@ -91,6 +192,22 @@ impl Default for CellGrid {
             escape_handler: Box::new(TimeEscape::default()),
             fire_influence: Default::default(),
             param_seed: None,
+            neighbourhood: Neighbourhood::default(),
+            density_speed: None,
+            move_resolution: MoveResolution::default(),
+            update_scheme: UpdateScheme::default(),
+            argument_policy: None,
+            strategy_representation: StrategyRepresentation::default(),
+            population_by_behaviour: Default::default(),
+            reputation: None,
+            exit_queues: None,
+            push_policy: None,
+            pressure_policy: None,
+            assistance: None,
+            groups: None,
+            wardens: None,
+            firefighters: None,
+            building: None,
             // inp_handlers : Default::default(),
             output_vars: Default::default(),
         }
@@ -194,33 +311,57 @@ impl CellGrid {
         });
         let mut hmap = HashSet::new();
         hmap.insert(fire_start);
-        let to_evac_grid = self
+        let mut to_evac_grid = self
             .initial_config
             .initial_evac_grid
             .clone()
             .unwrap_or_else(|| {
-                (0..self.initial_config.evac_num)
-                    .map(|_| {
+                let evac_num = self.initial_config.evac_num;
+                let sizes = self
+                    .groups
+                    .as_ref()
+                    .map_or_else(|| vec![1; evac_num], |g| g.policy.split(evac_num, rng));
+                let spread = self
+                    .groups
+                    .as_ref()
+                    .map_or(1, |g| (g.policy.max_distance / 2.).max(1.) as i32);
+                let mut first = (0, 0);
+                sizes
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(group, size)| (0..*size).map(move |member| (group, member)))
+                    .map(|(group, member)| {
                         let loc;
+                        let mut attempts = 0;
                         loop {
-                            let t = (
-                                rng.gen_range(0i32..self.dim.0 as i32),
-                                rng.gen_range(0i32..self.dim.0 as i32),
-                            );
-                            if !hmap.contains(&t) {
+                            // Group members start around the first one
+                            let t = if member > 0 && attempts < MAX_GROUP_PLACEMENT {
+                                attempts += 1;
+                                (
+                                    first.0 + rng.gen_range(-spread..=spread),
+                                    first.1 + rng.gen_range(-spread..=spread),
+                                )
+                            } else {
+                                (
+                                    rng.gen_range(0i32..self.dim.0 as i32),
+                                    rng.gen_range(0i32..self.dim.1 as i32),
+                                )
+                            };
+                            if within_bounds(t.0, self.dim.0 as i32)
+                                && within_bounds(t.1, self.dim.1 as i32)
+                                && !hmap.contains(&t)
+                            {
                                 hmap.insert(t);
                                 loc = t;
                                 break;
                             };
                         }
-                        let strat = rng.gen();
-                        let prob = rng.gen();
+                        if member == 0 {
+                            first = loc;
+                        }
                         EvacueeCell {
-                            strategy: strat,
-                            x: loc.0,
-                            y: loc.1,
-                            pr_c: prob,
-                            pr_d: prob,
+                            group: self.groups.as_ref().map(|_| group),
+                            ..self.initial_config.random_evacuee(loc, rng)
                         }
                     })
                     .collect_vec()
@@ -233,81 +374,551 @@ impl CellGrid {
             }
         }
 
-        for e in to_evac_grid.iter() {
+        // Evacuees of the upper floors get ids after the ones of the ground floor
+        let upper = match self.building.as_mut() {
+            Some(building) => {
+                let config = &self.initial_config;
+                building.populate(to_evac_grid.len(), rng, |loc, rng| {
+                    config.random_evacuee(loc, rng)
+                })
+            }
+            None => 0,
+        };
+        if let Some(firefighters) = self.firefighters.as_mut() {
+            firefighters.reset(to_evac_grid.len() + upper);
+        }
+        self.population_by_behaviour = Default::default();
+        for e in self.building.iter().flat_map(|b| b.evacuees()) {
+            self.population_by_behaviour[e.behaviour.index()] += 1;
+        }
+        for (id, e) in to_evac_grid.iter_mut().enumerate() {
+            e.id = id;
+            self.population_by_behaviour[e.behaviour.index()] += 1;
+            if let (Some(groups), Some(group)) = (self.groups.as_mut(), e.group) {
+                groups.add_member(group);
+            }
             self.evac_grid
                 .set_value_location(*e, &Int2D { x: e.x, y: e.y })
         }
         fire_start
     }
 
-    pub fn get_neigh(&self, x: i32, y: i32) -> Vec<Loc> {
-        let mut empty_vec = Vec::with_capacity(4);
-        for (i, j) in [(0, 1), (1, 0), (-1, 0), (0, -1)] {
-            let loc = Loc(x + i, y + j);
+    /// Available cells around `(x, y)` based on the movement neighbourhood, paired with the cost of the step
+    /// When moves are chained, cells held by other evacuees are available as well
+    pub fn get_neigh(&self, x: i32, y: i32) -> Vec<(Loc, f32)> {
+        let chain = self.move_resolution == MoveResolution::Chain;
+        self.available_cells(x, y, |loc| {
+            chain || self.evac_grid.get_value(&(*loc).into()).is_none()
+        })
+    }
+
+    /// Cells around `(x, y)` that are exits or empty cells within the grid for which `is_free` holds
+    pub fn available_cells(
+        &self,
+        x: i32,
+        y: i32,
+        is_free: impl Fn(&Loc) -> bool,
+    ) -> Vec<(Loc, f32)> {
+        let mut empty_vec = Vec::with_capacity(8);
+        for (loc, cost) in self.neighbourhood.neighbours(&Loc(x, y)) {
             if self.escape_handler.is_exit(&loc)
-                || (within_bounds(x + i, self.dim.0 as i32)
-                && within_bounds(y + j, self.dim.1 as i32) // if we are not out of bounds
-                && self
-                    .evac_grid
-                    .get_value(&loc.into())
-                    .is_none()
+                || (within_bounds(loc.0, self.dim.0 as i32)
+                && within_bounds(loc.1, self.dim.1 as i32) // if we are not out of bounds
+                && is_free(&loc)
                 && self.grid.get_value_unbuffered(&loc.into()).unwrap() == CellType::Empty)
             // if the cell is empty
             // if there are no evacuees
             {
-                empty_vec.push(loc)
+                empty_vec.push((loc, cost))
             }
         }
         empty_vec
     }
 
+    /// Number of evacuees and number of cells within the square window of half width `radius` around `(x, y)`
+    pub fn local_occupancy(&self, x: i32, y: i32, radius: i32) -> (usize, usize) {
+        let mut occupied = 0;
+        let mut window = 0;
+        for i in -radius..=radius {
+            for j in -radius..=radius {
+                if !within_bounds(x + i, self.dim.0 as i32)
+                    || !within_bounds(y + j, self.dim.1 as i32)
+                {
+                    continue;
+                }
+                window += 1;
+                if self
+                    .evac_grid
+                    .get_value(&Int2D { x: x + i, y: y + j })
+                    .is_some()
+                {
+                    occupied += 1;
+                }
+            }
+        }
+        (occupied, window)
+    }
+
+    /// Strategies of the evacuees in the Moore neighbourhood of `(x, y)`
+    fn neighbour_strategies(&self, x: i32, y: i32) -> Vec<Strategy> {
+        Neighbourhood::Moore
+            .neighbours(&Loc(x, y))
+            .filter(|(Loc(i, j), _)| {
+                within_bounds(*i, self.dim.0 as i32) && within_bounds(*j, self.dim.1 as i32)
+            })
+            .filter_map(|(loc, _)| self.evac_grid.get_value(&loc.into()).map(|e| e.strategy))
+            .collect_vec()
+    }
+
+    /// Last payoff and strategy of the evacuees in the Moore neighbourhood of `(x, y)`
+    fn neighbour_payoffs(&self, x: i32, y: i32) -> Vec<(f32, Strategy)> {
+        Neighbourhood::Moore
+            .neighbours(&Loc(x, y))
+            .filter(|(Loc(i, j), _)| {
+                within_bounds(*i, self.dim.0 as i32) && within_bounds(*j, self.dim.1 as i32)
+            })
+            .filter_map(|(loc, _)| {
+                self.evac_grid
+                    .get_value(&loc.into())
+                    .map(|e| (e.payoff, e.strategy))
+            })
+            .collect_vec()
+    }
+
+    /// Evacuees within `radius` of `centre`
+    fn evacuees_within(&self, centre: Loc, radius: f32) -> Vec<EvacueeCell> {
+        let r = radius.max(0.) as i32;
+        (-r..=r)
+            .cartesian_product(-r..=r)
+            .filter(|(dx, dy)| (dx * dx + dy * dy) as f32 <= radius * radius)
+            .map(|(dx, dy)| Loc(centre.0 + dx, centre.1 + dy))
+            .filter(|Loc(i, j)| {
+                within_bounds(*i, self.dim.0 as i32) && within_bounds(*j, self.dim.1 as i32)
+            })
+            .filter_map(|loc| self.evac_grid.get_value(&loc.into()))
+            .collect_vec()
+    }
+
+    /// Strategies of the evacuees within `radius` of `centre`, except the ones in `excluded`
+    fn group_strategies(&self, centre: Loc, radius: f32, excluded: &[usize]) -> Vec<Strategy> {
+        self.evacuees_within(centre, radius)
+            .into_iter()
+            .filter(|e| !excluded.contains(&e.id))
+            .map(|e| e.strategy)
+            .collect_vec()
+    }
+
+    /// Queue up the evacuees close to every exit in order of arrival
+    fn update_queues(&mut self) {
+        let Some(radius) = self.exit_queues.as_ref().map(|q| q.policy.radius) else {
+            return;
+        };
+        for exit in self.escape_handler.exits() {
+            let mut present = self.evacuees_within(exit, radius);
+            // Evacuees arriving together queue up by distance to the exit
+            present.sort_by_key(|e| (e.x - exit.0).pow(2) + (e.y - exit.1).pow(2));
+            let present = present
+                .into_iter()
+                .map(|e| (e.id, (e.x - exit.0).pow(2) + (e.y - exit.1).pow(2)))
+                .collect_vec();
+            if let Some(queues) = self.exit_queues.as_mut() {
+                queues.update(exit, &present, self.step);
+            }
+        }
+    }
+
+    /// Let the first of the queue through a cell in the queue zone of an exit.
+    /// Competitive evacuees taking the cell ahead of a queued cooperative one pay the jump cost
+    fn queue_order(
+        &mut self,
+        dist: Loc,
+        game: RuleCase,
+        reward_b: f32,
+        lis: &mut [(Loc, Outcome)],
+        rstps: &HashMap<Loc, RSTP>,
+        asps: &HashMap<usize, f32>,
+    ) {
+        let Some(queues) = self.exit_queues.as_ref() else {
+            return;
+        };
+        let Some(exit) = queues.zone(&self.escape_handler.exits(), dist) else {
+            return;
+        };
+        if lis.first().is_none_or(|(c, _)| *c != dist) {
+            // Nobody gets the cell
+            return;
+        }
+        let rank = |e: &EvacueeCell| queues.position(exit, e.id).unwrap_or(usize::MAX);
+        if game == RuleCase::AllCoop {
+            let (first, _) = lis
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, (_, (_, _, e)))| rank(e))
+                .unwrap();
+            let Loc(x, y) = {
+                let (_, (_, _, e)) = &lis[0];
+                Loc(e.x, e.y)
+            };
+            lis[0].0 = Loc(x, y);
+            lis[first].0 = dist;
+            lis.swap(0, first);
+            return;
+        }
+        let winner_rank = rank(&lis[0].1 .2);
+        let jumped = lis[1..]
+            .iter()
+            .any(|(_, (_, _, e))| e.strategy == Strategy::Cooperative && rank(e) < winner_rank);
+        if jumped {
+            let jump_cost = queues.policy.jump_cost * reward_b;
+            let (_, (stim, payoff, e)) = &mut lis[0];
+            *payoff -= jump_cost;
+            *stim = s_x(rstps[&Loc(e.x, e.y)], asps[&e.id], *payoff);
+            if let Some(queues) = self.exit_queues.as_mut() {
+                queues.jumps += 1;
+            }
+        }
+    }
+
+    /// Cells that keep a queued cooperative evacuee behind the one waiting ahead of him
+    fn queue_cells(&self, val: &EvacueeCell, cells: Vec<(Loc, f32)>) -> Vec<(Loc, f32)> {
+        let Some(queues) = self.exit_queues.as_ref() else {
+            return cells;
+        };
+        if val.strategy != Strategy::Cooperative {
+            return cells;
+        }
+        let exit = queues.zone(&self.escape_handler.exits(), Loc(val.x, val.y));
+        let Some((exit, ahead)) = exit.and_then(|e| Some((e, queues.ahead(e, val.id)?))) else {
+            return cells;
+        };
+        cells
+            .into_iter()
+            .filter(|(Loc(x, y), _)| (x - exit.0).pow(2) + (y - exit.1).pow(2) > ahead)
+            .collect_vec()
+    }
+
+    /// Every evacuee in the building
+    #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+    fn all_evacuees(&self) -> Vec<EvacueeCell> {
+        self.evac_grid
+            .locs
+            .values()
+            .iter()
+            .map(|e| **e)
+            .collect_vec()
+    }
+
+    /// Every evacuee in the building
+    #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+    fn all_evacuees(&self) -> Vec<EvacueeCell> {
+        let all = RefCell::new(vec![]);
+        self.evac_grid.iter_values(|_, e| all.borrow_mut().push(*e));
+        all.take()
+    }
+
+    /// Share the strategy of every group and hold back the members ahead of a straggler
+    fn update_groups(&mut self) {
+        if self.groups.is_none() {
+            return;
+        }
+        let members = self
+            .all_evacuees()
+            .into_iter()
+            .filter_map(|e| {
+                Some(Member {
+                    id: e.id,
+                    group: e.group?,
+                    loc: Loc(e.x, e.y),
+                    exit_dist: self
+                        .static_influence
+                        .static_influence(&Int2D { x: e.x, y: e.y }),
+                    strategy: e.strategy,
+                })
+            })
+            .collect_vec();
+        if let Some(groups) = self.groups.as_mut() {
+            groups.update(&members);
+        }
+    }
+
+    /// Locate the wardens and let every one of them pick the exit it directs the evacuees to,
+    /// exits on fire are avoided
+    fn update_wardens(&mut self) {
+        if self.wardens.is_none() {
+            return;
+        }
+        let exits = self
+            .escape_handler
+            .exits()
+            .into_iter()
+            .filter(|e| self.grid.get_value(&(*e).into()) != Some(CellType::Fire))
+            .map(|e| {
+                let fire = self.fire_influence.fire_state.closest_point(&e);
+                (e, fire.map(f32::sqrt))
+            })
+            .collect_vec();
+        let (wardens, others): (Vec<_>, Vec<_>) = self
+            .all_evacuees()
+            .into_iter()
+            .partition(|e| e.behaviour == Behaviour::Warden);
+        let posts = wardens
+            .into_iter()
+            .filter_map(|e| {
+                let loc = Loc(e.x, e.y);
+                Wardens::choose_exit(loc, &exits).map(|exit| (loc, exit))
+            })
+            .collect_vec();
+        let others = others.into_iter().map(|e| Loc(e.x, e.y)).collect_vec();
+        if let Some(wardens) = self.wardens.as_mut() {
+            wardens.update(posts, &others);
+        }
+    }
+
+    /// Strategy played in a contest by `evac` according to its behavioural type,
+    /// group members start from the strategy shared by their group
+    fn contest_strategy(&self, evac: &EvacueeCell) -> Strategy {
+        let here = Int2D {
+            x: evac.x,
+            y: evac.y,
+        };
+        let radius = self.initial_config.free_rider_radius;
+        let near_exit = self
+            .escape_handler
+            .exits()
+            .iter()
+            .any(|exit| distsq(&here, &(*exit).into()) <= radius.powi(2));
+        let neighbours = if evac.behaviour == Behaviour::Follower {
+            self.neighbour_strategies(evac.x, evac.y)
+        } else {
+            vec![]
+        };
+        let strategy = evac
+            .group
+            .and_then(|g| self.groups.as_ref()?.shared_strategy(g))
+            .unwrap_or(evac.strategy);
+        evac.behaviour
+            .contest_strategy(strategy, &neighbours, near_exit)
+    }
+
+    /// Sample the cell the evacuee wants to move to.
+    /// `None` if there are no available cells, the crowd around the evacuee held him back
+    /// or he waits for a straggler of his group or for the evacuee ahead of him in the queue
+    fn choose_move(
+        &self,
+        val: &EvacueeCell,
+        evacuee_agent: &EvacueeAgent,
+        rng: &mut impl RngCore,
+    ) -> Option<Loc> {
+        self.choose_move_among(val, self.get_neigh(val.x, val.y), evacuee_agent, rng)
+    }
+
+    /// Sample the cell the evacuee wants to move to out of `empty_cells`
+    fn choose_move_among(
+        &self,
+        val: &EvacueeCell,
+        empty_cells: Vec<(Loc, f32)>,
+        evacuee_agent: &EvacueeAgent,
+        rng: &mut impl RngCore,
+    ) -> Option<Loc> {
+        if self.groups.as_ref().is_some_and(|g| g.is_waiting(val.id)) {
+            return None;
+        }
+        if let Some(density_speed) = &self.density_speed {
+            let (occupied, window) = self.local_occupancy(val.x, val.y, density_speed.radius);
+            let prob = density_speed.move_probability(occupied, window);
+            if !rng.gen_bool(prob as f64) {
+                return None;
+            }
+        }
+        if empty_cells.is_empty() {
+            return None;
+        }
+        if val.behaviour == Behaviour::Firefighter {
+            return self.firefighter_move(val, empty_cells, rng);
+        }
+        let empty_cells = self.queue_cells(val, empty_cells);
+        if empty_cells.is_empty() {
+            return None;
+        }
+        let guide = self
+            .wardens
+            .as_ref()
+            .and_then(|w| w.guide(Loc(val.x, val.y)));
+        let weights = evacuee_agent.calculate_probabilities(
+            // else calculate the probability distribution of the neighbouring cells
+            &empty_cells,
+            self.static_influence.as_ref(),
+            &self.fire_influence,
+            guide,
+        );
+        // dbg!(&weights);
+        let dist = WeightedIndex::new(&weights).expect("Weights are not valid");
+        Some(empty_cells[dist.sample(rng)].0)
+    }
+
+    /// Firefighters next to the fire stay to fight it, the others head for the fire avoiding the exits
+    fn firefighter_move(
+        &self,
+        val: &EvacueeCell,
+        empty_cells: Vec<(Loc, f32)>,
+        rng: &mut impl RngCore,
+    ) -> Option<Loc> {
+        if !self.burning_around(Loc(val.x, val.y)).is_empty() {
+            return None;
+        }
+        let cells = empty_cells
+            .into_iter()
+            .filter(|(loc, _)| {
+                !self.escape_handler.is_exit(loc)
+                    && self.grid.get_value(&(*loc).into()) != Some(CellType::Fire)
+            })
+            .collect_vec();
+        if cells.is_empty() {
+            return None;
+        }
+        let weights = Firefighters::move_weights(&cells, |loc| {
+            self.fire_influence.fire_state.closest_point(loc)
+        });
+        let dist = WeightedIndex::new(&weights).expect("Weights are not valid");
+        Some(cells[dist.sample(rng)].0)
+    }
+
+    /// Burning cells in the Moore neighbourhood of `loc`, `loc` included
+    fn burning_around(&self, loc: Loc) -> Vec<Loc> {
+        Neighbourhood::Moore
+            .neighbours(&loc)
+            .map(|(l, _)| l)
+            .chain([loc])
+            .filter(|Loc(i, j)| {
+                within_bounds(*i, self.dim.0 as i32) && within_bounds(*j, self.dim.1 as i32)
+            })
+            .filter(|l| self.grid.get_value(&(*l).into()) == Some(CellType::Fire))
+            .collect_vec()
+    }
+
+    /// Send the firefighters in through the free cells next to the exits once they arrive,
+    /// firefighters next to the fire put out the burning cells around them
+    fn deploy_firefighters(&mut self, evacuees: &mut Vec<EvacueeCell>) {
+        if self.firefighters.is_none() {
+            return;
+        }
+        let occupied: HashSet<Loc> = evacuees.iter().map(|e| Loc(e.x, e.y)).collect();
+        let entries = self
+            .escape_handler
+            .exits()
+            .into_iter()
+            .flat_map(|exit| Neighbourhood::Moore.neighbours(&exit).map(|(l, _)| l))
+            .filter(|l| {
+                within_bounds(l.0, self.dim.0 as i32)
+                    && within_bounds(l.1, self.dim.1 as i32)
+                    && !self.escape_handler.is_exit(l)
+                    && self.grid.get_value(&(*l).into()) != Some(CellType::Fire)
+            })
+            .unique()
+            .filter(|l| !occupied.contains(l))
+            .collect_vec();
+        let burning = evacuees
+            .iter()
+            .filter(|e| e.behaviour == Behaviour::Firefighter)
+            .map(|e| self.burning_around(Loc(e.x, e.y)))
+            .collect_vec();
+        let step = self.step;
+        let Some(firefighters) = self.firefighters.as_mut() else {
+            return;
+        };
+        let ids = firefighters.dispatch(step, entries.len());
+        for (id, loc) in ids.into_iter().zip(entries) {
+            evacuees.push(EvacueeCell {
+                id,
+                strategy: Strategy::Competitive,
+                x: loc.0,
+                y: loc.1,
+                behaviour: Behaviour::Firefighter,
+                ..Default::default()
+            });
+        }
+        for cells in burning {
+            firefighters.suppress(cells.into_iter());
+        }
+    }
+
+    /// Move the evacuees of the upper floors, the ones reaching the bottom of a stairwell
+    /// step into the ground floor when its stair cell is free and die if it is burning
+    fn descend_stairs(&mut self, evacuees: &mut Vec<EvacueeCell>, rng: &mut dyn RngCore) {
+        let Some(building) = self.building.as_mut() else {
+            return;
+        };
+        let occupied: HashSet<Loc> = evacuees.iter().map(|e| Loc(e.x, e.y)).collect();
+        let grid = &self.grid;
+        let burning = |l: &Loc| grid.get_value(&(*l).into()) == Some(CellType::Fire);
+        let (arrivals, dead) = building.evacuee_step(
+            self.step,
+            |l| !occupied.contains(l) && !burning(l),
+            burning,
+            rng,
+        );
+        evacuees.extend(arrivals);
+        for loc in dead {
+            self.death_handler
+                .update_death(loc.into(), DeathCause::Fire);
+        }
+    }
+
+    /// Collect the intended movement of every evacuee that can take a sub-step
+    /// `new_tick` - Add the movement of a new tick to the budget of every evacuee
     #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
     fn get_distinations(
         &mut self,
         evacuee_agent: &EvacueeAgent,
         rng: &mut impl RngCore,
+        new_tick: bool,
     ) -> (HashMap<Loc, Vec<EvacueeCell>>, Vec<EvacueeCell>) {
         let mut updates = HashMap::new();
         let mut still = vec![];
         // Extract intended movements of every agent, if agents want to move to the same square, add them to the queue
         for val in self.evac_grid.locs.values().iter().map(|c| *c) {
             let loc = Int2D { x: val.x, y: val.y };
-            if *self.grid.locs.get_read(&loc).unwrap() == CellType::Fire {
-                self.death_handler.update_death(loc);
+            if *self.grid.locs.get_read(&loc).unwrap() == CellType::Fire
+                && val.behaviour != Behaviour::Firefighter
+            {
+                self.death_handler.update_death(loc, DeathCause::Fire);
                 continue;
             }
-            let empty_cells = self.get_neigh(val.x, val.y);
-            if empty_cells.len() == 0 {
-                // If there are no available cells, stay still
-                still.push(*val);
+            let mut val = *val;
+            if new_tick {
+                val.accumulate_budget();
+            }
+            if !val.can_move() {
+                // Slow evacuees skip the (sub-)step
+                still.push(val);
                 continue;
             }
-            let weights = evacuee_agent.calculate_probabilities(
-                // else calculate the probability distribution of the neighbouring cells
-                &empty_cells,
-                self.static_influence.as_ref(),
-                &self.fire_influence,
-            );
-            // dbg!(&weights);
-            let dist = WeightedIndex::new(weights).unwrap();
-            let opted_dist = empty_cells[dist.sample(rng)];
+            val.spend_substep();
+            let Some(opted_dist) = self.choose_move(&val, evacuee_agent, rng) else {
+                // If there are no available cells, stay still
+                still.push(val);
+                continue;
+            };
             if self.escape_handler.is_exit(&opted_dist) {
-                self.escape_handler.escaped(*val, self.step as usize);
+                self.escape_handler.escaped(val, self.step as usize);
             } else {
                 updates // look for opted disk in the hashmap
                     .entry(opted_dist)
-                    .and_modify(|c: &mut Vec<EvacueeCell>| c.push(*val)) // if it exists, add the evacuee who wants to occupy the wanted square to the queue
-                    .or_insert(vec![*val]); // else create a new vector with the evacuee in
+                    .and_modify(|c: &mut Vec<EvacueeCell>| c.push(val)) // if it exists, add the evacuee who wants to occupy the wanted square to the queue
+                    .or_insert(vec![val]); // else create a new vector with the evacuee in
             }
         }
         (updates, still)
     }
 
+    /// Collect the intended movement of every evacuee that can take a sub-step
+    /// `new_tick` - Add the movement of a new tick to the budget of every evacuee
     #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
     fn get_distinations(
         &mut self,
         evacuee_agent: &EvacueeAgent,
         rng: &mut impl RngCore,
+        new_tick: bool,
     ) -> (HashMap<Loc, Vec<EvacueeCell>>, Vec<EvacueeCell>) {
         let updates = RefCell::new(HashMap::new());
         let still = RefCell::new(vec![]);
@@ -316,41 +927,43 @@ impl CellGrid {
         let rng = RefCell::new(rng);
         // Extract intended movements of every agent, if agents want to move to the same square, add them to the queue
         self.evac_grid.iter_values(|loc, val| {
-            if self.grid.get_value(loc).unwrap() == CellType::Fire {
+            if self.grid.get_value(loc).unwrap() == CellType::Fire
+                && val.behaviour != Behaviour::Firefighter
+            {
                 //death_handler.update_death(*loc);
                 dead.borrow_mut().push(*loc);
                 return;
             }
-            let empty_cells = self.get_neigh(val.x, val.y);
-            if empty_cells.len() == 0 {
-                // If there are no available cells, stay still
-                still.borrow_mut().push(*val);
+            let mut val = *val;
+            if new_tick {
+                val.accumulate_budget();
+            }
+            if !val.can_move() {
+                // Slow evacuees skip the (sub-)step
+                still.borrow_mut().push(val);
                 return;
             }
-            let weights = evacuee_agent.calculate_probabilities(
-                // else calculate the probability distribution of the neighbouring cells
-                &empty_cells,
-                self.static_influence.as_ref(),
-                &self.fire_influence,
-            );
-            // dbg!(&weights);
-            let dist = WeightedIndex::new(&weights).expect("Weights are not valid");
-            let opted_dist = empty_cells[dist.sample(*rng.borrow_mut())];
+            val.spend_substep();
+            let Some(opted_dist) = self.choose_move(&val, evacuee_agent, *rng.borrow_mut()) else {
+                // If there are no available cells, stay still
+                still.borrow_mut().push(val);
+                return;
+            };
             if self.escape_handler.is_exit(&opted_dist) {
                 // self.escape_handler.escaped(*val, self.step as usize);
-                escape.borrow_mut().push((*val, self.step as usize))
+                escape.borrow_mut().push((val, self.step as usize))
             } else {
                 updates // look for opted disk in the hashmap
                     .borrow_mut()
                     .entry(opted_dist)
-                    .and_modify(|c: &mut Vec<EvacueeCell>| c.push(*val)) // if it exists, add the evacuee who wants to occupy the wanted square to the queue
-                    .or_insert(vec![*val]); // else create a new vector with the evacuee in
+                    .and_modify(|c: &mut Vec<EvacueeCell>| c.push(val)) // if it exists, add the evacuee who wants to occupy the wanted square to the queue
+                    .or_insert(vec![val]); // else create a new vector with the evacuee in
             }
         });
         // self.file_handler.curr_line.escaped += escape.borrow().len();
         // self.file_handler.curr_line.dead += dead.borrow().len();
         for loc in dead.take().into_iter() {
-            self.death_handler.update_death(loc);
+            self.death_handler.update_death(loc, DeathCause::Fire);
         }
 
         for (val, step) in escape.take().into_iter() {
@@ -362,7 +975,7 @@ impl CellGrid {
     fn play_game(
         &mut self,
         dist: Loc,
-        competing: Vec<EvacueeCell>,
+        mut competing: Vec<EvacueeCell>,
         rng: &mut impl RngCore,
         evac_agent: &EvacueeAgent,
     ) -> Vec<EvacueeCell> {
@@ -392,6 +1005,38 @@ impl CellGrid {
                 csv:true
             );
         }
+        let continuous = self.strategy_representation == StrategyRepresentation::Continuous;
+        if continuous {
+            // Mixed strategies, every contestant plays an action drawn from its cooperation level
+            for e in competing.iter_mut() {
+                e.strategy = sample_action(e.coop_level, rng);
+            }
+        }
+        for e in competing.iter_mut() {
+            e.strategy = self.contest_strategy(e);
+        }
+        if let Some(wardens) = self.wardens.as_ref() {
+            // Adaptive evacuees close to a warden may be talked into cooperating
+            for e in competing.iter_mut().filter(|e| e.behaviour.adaptive()) {
+                if e.strategy == Strategy::Competitive
+                    && wardens.guide(Loc(e.x, e.y)).is_some()
+                    && rng.gen::<f32>() < wardens.policy.persuasion
+                {
+                    e.strategy = Strategy::Cooperative;
+                    self.output_vars.persuaded += 1;
+                }
+            }
+        }
+        if let Some(reputation) = self.reputation.as_mut() {
+            // Adaptive evacuees reciprocate against the opponents they remember
+            let ids = competing.iter().map(|e| e.id).collect_vec();
+            for e in competing.iter_mut().filter(|e| e.behaviour.adaptive()) {
+                let opponents = ids.iter().copied().filter(|o| *o != e.id).collect_vec();
+                e.strategy = reputation.choose(e.id, &opponents, e.strategy);
+            }
+            let played = competing.iter().map(|e| (e.id, e.strategy)).collect_vec();
+            reputation.record_contest(&played);
+        }
         // Could be optimised with no need to return new location
         let game = competing[0].strategy.game_rules(
             // this section returns a shuffled list, the first is the user who will occupy the square where the rest will wait
@@ -402,15 +1047,9 @@ impl CellGrid {
                 .collect::<Vec<_>>(),
         );
         match &game {
-            super::evacuee_mod::strategy::RuleCase::AllCoop => {
-                self.output_vars.per_case_ratio_1 += 1
-            }
-            super::evacuee_mod::strategy::RuleCase::AllButOneCoop => {
-                self.output_vars.per_case_ratio_2 += 1
-            }
-            super::evacuee_mod::strategy::RuleCase::Argument => {
-                self.output_vars.per_case_ratio_3 += 1
-            }
+            RuleCase::AllCoop => self.output_vars.per_case_ratio_1 += 1,
+            RuleCase::AllButOneCoop => self.output_vars.per_case_ratio_2 += 1,
+            RuleCase::Argument => self.output_vars.per_case_ratio_3 += 1,
         }
         let n = competing.len();
         let competing: Vec<_> = competing
@@ -423,27 +1062,86 @@ impl CellGrid {
                 )
             })
             .collect();
+        let rstps: HashMap<Loc, RSTP> =
+            competing.iter().map(|(w, e)| (Loc(e.x, e.y), *w)).collect();
+
+        // Aspiration of every contestant, the global one unless aspirations are per evacuee
+        let asps: HashMap<usize, f32> = competing
+            .iter()
+            .map(|(_, e)| {
+                (
+                    e.id,
+                    self.fire_influence.calculate_agent_aspiration(e.aspiration),
+                )
+            })
+            .collect();
 
-        let asp = self.fire_influence.calculate_aspiration();
+        // N-player game, every evacuee around the contested cell plays along with the contestants
+        let group_play = self.fire_influence.game.group_radius().map(|radius| {
+            let ids = competing.iter().map(|(_, e)| e.id).collect_vec();
+            let bystanders = self.group_strategies(dist, radius, &ids);
+            let group = competing.len() + bystanders.len();
+            let cooperators = competing
+                .iter()
+                .map(|(_, e)| e.strategy)
+                .chain(bystanders)
+                .filter(|s| *s == Strategy::Cooperative)
+                .count();
+            (group, cooperators)
+        });
 
         // self.file_handler.curr_line.asp = asp;
-        let ids = rules(game, competing, rng, asp);
+        let game_type = self.fire_influence.game;
+        let policy = self.argument_policy.unwrap_or(ArgumentPolicy {
+            resolution: group_play.map_or_else(
+                || game_type.argument_resolution(),
+                |(group, cooperators)| game_type.group_resolution(cooperators, group),
+            ),
+            injury_risk: 0.,
+        });
+        let asp_of = {
+            let asps = asps.clone();
+            move |e: &EvacueeCell| asps[&e.id]
+        };
+        let ids = rules(game, competing, rng, asp_of, policy.resolution);
         let lis: Box<dyn Iterator<Item = _>> = if let Ok(mut ids) = ids {
             Box::new(
                 [(dist, ids.next().unwrap())]
                     .into_iter()
-                    .chain(ids.map(|(d, c)| (Loc(c.x, c.y), (d, c)))),
+                    .chain(ids.map(|(d, p, c)| (Loc(c.x, c.y), (d, p, c)))),
             )
         } else {
             Box::new(
                 ids.err()
                     .unwrap()
                     // .into_iter()
-                    .map(|(d, c)| (Loc(c.x, c.y), (d, c))),
+                    .map(|(d, p, c)| (Loc(c.x, c.y), (d, p, c))),
             )
         };
-        lis.map(|(c, (stim, mut evac))| {
-            // self.file_handler.curr_line.reward.update(stim);
+        let mut lis = lis.collect_vec();
+        if continuous {
+            // The stimulus is the expected payoff given the cooperation levels of the contestants
+            let total: f32 = lis.iter().map(|(_, (_, _, e))| e.coop_level).sum();
+            for (_, (stim, payoff, e)) in lis.iter_mut() {
+                let others = (total - e.coop_level) / (n - 1) as f32;
+                let rstp = rstps[&Loc(e.x, e.y)];
+                *payoff = expected_payoff(rstp, e.coop_level, others);
+                *stim = s_x(rstp, asps[&e.id], *payoff);
+            }
+        }
+        if let Some((group, cooperators)) = group_play {
+            // The payoffs depend on every cooperator around the contested cell
+            for (_, (stim, payoff, e)) in lis.iter_mut() {
+                let rstp = self
+                    .fire_influence
+                    .calculcate_rewards(group, &Loc(e.x, e.y), reward_b);
+                let others = cooperators - (e.strategy == Strategy::Cooperative) as usize;
+                *payoff = self
+                    .fire_influence
+                    .game
+                    .group_payoff(rstp, e.strategy, others, group);
+                *stim = s_x(rstp, asps[&e.id], *payoff);
+            }
             #[cfg(not(any(
                 feature = "visualization",
                 feature = "visualization_wasm",
@@ -452,32 +1150,469 @@ impl CellGrid {
             )))]
             {
                 plot!(
-                    "RewardAspiration".to_owned(),
-                    format!("{game:?}"),
-                    round(asp as f64,3) ,
-                    round(stim as f64,3),
+                    "GroupCooperation".to_owned(),
+                    "series".to_owned(),
+                    group as f64,
+                    round(cooperators as f64 / group as f64, 3),
                     csv:true
                 );
             }
-            evac_agent.calculate_strategies(&mut evac, rng, stim);
-            evac.x = c.0;
-            evac.y = c.1;
-            evac
-        })
-        .collect()
+        }
+        self.queue_order(dist, game, reward_b, &mut lis, &rstps, &asps);
+        let mut result = lis
+            .into_iter()
+            .map(|(c, (stim, payoff, mut evac))| {
+                // self.file_handler.curr_line.reward.update(stim);
+                #[cfg(not(any(
+                    feature = "visualization",
+                    feature = "visualization_wasm",
+                    feature = "bayesian",
+                    feature = "ga_search"
+                )))]
+                {
+                    plot!(
+                        "RewardAspiration".to_owned(),
+                        format!("{game:?}"),
+                        round(asps[&evac.id] as f64,3) ,
+                        round(stim as f64,3),
+                        csv:true
+                    );
+                }
+                evac.payoff = payoff;
+                if !evac.behaviour.adaptive() {
+                    // Fixed behavioural types do not learn
+                } else if continuous {
+                    evac_agent.calculate_level(&mut evac, stim);
+                } else {
+                    let peers = self.neighbour_payoffs(evac.x, evac.y);
+                    evac_agent.calculate_strategies(&mut evac, rng, stim, &peers);
+                }
+                evac.aspiration = self
+                    .fire_influence
+                    .aspiration
+                    .habituate(evac.aspiration, payoff);
+                evac.x = c.0;
+                evac.y = c.1;
+                evac
+            })
+            .collect::<Vec<_>>();
+        if game == RuleCase::Argument {
+            self.settle_argument(&policy, dist, &mut result, rng);
+        }
+        result
     }
 
-    pub fn evacuee_step(&mut self, evacuee_agent: &EvacueeAgent, rng: &mut impl RngCore) {
-        let (updates, still) = self.get_distinations(evacuee_agent, rng);
-        let lp = updates //calculates which agent will occupy their intended square based on their game rules and preferences
+    /// Apply the time penalty and injuries of an argument over `dist` to the evacuees other than
+    /// firefighters and log its outcome. If the argument has a winner, it is the first evacuee of `result`
+    fn settle_argument(
+        &mut self,
+        policy: &ArgumentPolicy,
+        dist: Loc,
+        result: &mut [EvacueeCell],
+        rng: &mut impl RngCore,
+    ) {
+        let won = result.first().is_some_and(|e| Loc(e.x, e.y) == dist);
+        let mut injuries = 0;
+        for (i, evac) in result.iter_mut().enumerate() {
+            if evac.behaviour == Behaviour::Firefighter {
+                // Firefighters are neither held up nor hurt by the crowd
+                continue;
+            }
+            if !(won && i == 0) {
+                evac.penalise(policy.resolution.penalty());
+            }
+            if !evac.injured && rng.gen::<f32>() < policy.injury_risk {
+                evac.injure();
+                let loc = Int2D {
+                    x: evac.x,
+                    y: evac.y,
+                };
+                self.death_handler.update_injury(loc, DeathCause::Argument);
+                injuries += 1;
+            }
+        }
+        if won {
+            self.output_vars.arguments_won += 1;
+        } else {
+            self.output_vars.arguments_blocked += 1;
+        }
+        self.output_vars.argument_injuries += injuries;
+        #[cfg(not(any(
+            feature = "visualization",
+            feature = "visualization_wasm",
+            feature = "bayesian",
+            feature = "ga_search"
+        )))]
+        {
+            plot!(
+                "ArgumentOutcome".to_owned(),
+                if won { "Won" } else { "Blocked" }.to_owned(),
+                self.step as f64,
+                injuries as f64,
+                csv:true
+            );
+        }
+    }
+
+    /// Parallel update, every evacuee picks a cell at once and conflicts are resolved through `play_game`
+    fn parallel_step(
+        &mut self,
+        evacuee_agent: &EvacueeAgent,
+        rng: &mut impl RngCore,
+        new_tick: bool,
+    ) -> Vec<EvacueeCell> {
+        let (updates, still) = self.get_distinations(evacuee_agent, rng, new_tick);
+        let mut placed = updates //calculates which agent will occupy their intended square based on their game rules and preferences
             .into_iter()
-            .flat_map(|(dist, competing)| self.play_game(dist, competing, rng, evacuee_agent))
-            .chain(still.into_iter())
+            .flat_map(|(dist, competing)| {
+                let origins = competing.iter().map(|e| Loc(e.x, e.y)).collect_vec();
+                let result = self.play_game(dist, competing, rng, evacuee_agent);
+                with_origins(dist, &origins, result)
+            })
+            .chain(still.into_iter().map(|e| (Loc(e.x, e.y), e)))
             .collect::<Vec<_>>(); // .into_iter();
-        for e in lp {
-            self.evac_grid
-                .set_value_location(e, &Int2D { x: e.x, y: e.y })
+        if self.move_resolution == MoveResolution::Chain {
+            // Only allow moves into occupied cells whose occupant is leaving
+            resolve_chain(&mut placed);
+        }
+        placed.into_iter().map(|(_, e)| e).collect_vec()
+    }
+
+    /// Evacuees that are still alive, evacuees standing on fire are handed to the death handler
+    /// `new_tick` - Add the movement of a new tick to the budget of every evacuee
+    #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
+    fn collect_evacuees(&mut self, new_tick: bool) -> Vec<EvacueeCell> {
+        let mut alive = vec![];
+        let mut dead = vec![];
+        for val in self.evac_grid.locs.values().iter().map(|c| *c) {
+            let loc = Int2D { x: val.x, y: val.y };
+            if *self.grid.locs.get_read(&loc).unwrap() == CellType::Fire
+                && val.behaviour != Behaviour::Firefighter
+            {
+                dead.push(loc);
+                continue;
+            }
+            let mut val = *val;
+            if new_tick {
+                val.accumulate_budget();
+            }
+            alive.push(val);
+        }
+        for loc in dead.into_iter() {
+            self.death_handler.update_death(loc, DeathCause::Fire);
         }
+        alive
+    }
+
+    /// Evacuees that are still alive, evacuees standing on fire are handed to the death handler
+    /// `new_tick` - Add the movement of a new tick to the budget of every evacuee
+    #[cfg(not(any(feature = "visualization", feature = "visualization_wasm")))]
+    fn collect_evacuees(&mut self, new_tick: bool) -> Vec<EvacueeCell> {
+        let alive = RefCell::new(vec![]);
+        let dead = RefCell::new(vec![]);
+        self.evac_grid.iter_values(|loc, val| {
+            if self.grid.get_value(loc).unwrap() == CellType::Fire
+                && val.behaviour != Behaviour::Firefighter
+            {
+                dead.borrow_mut().push(*loc);
+                return;
+            }
+            let mut val = *val;
+            if new_tick {
+                val.accumulate_budget();
+            }
+            alive.borrow_mut().push(val);
+        });
+        for loc in dead.take().into_iter() {
+            self.death_handler.update_death(loc, DeathCause::Fire);
+        }
+        alive.take()
+    }
+
+    /// Sequential update, evacuees move one at a time in the order of the update scheme
+    /// and every evacuee sees the moves made before him. Cells are never contested so no game is played
+    fn sequential_step(
+        &mut self,
+        evacuee_agent: &EvacueeAgent,
+        rng: &mut impl RngCore,
+        new_tick: bool,
+    ) -> Vec<EvacueeCell> {
+        let mut evacuees = self.collect_evacuees(new_tick);
+        let order = self.update_scheme.order(&evacuees, rng);
+        let escaped = sequential_moves(
+            &mut evacuees,
+            &order,
+            |val, occupied| {
+                let empty_cells = self.available_cells(val.x, val.y, |loc| !occupied.contains(loc));
+                self.choose_move_among(val, empty_cells, evacuee_agent, rng)
+            },
+            |loc| self.escape_handler.is_exit(loc),
+        );
+        for idx in escaped.iter() {
+            self.escape_handler
+                .escaped(evacuees[*idx], self.step as usize);
+        }
+        evacuees
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, e)| (!escaped.contains(&idx)).then_some(e))
+            .collect_vec()
+    }
+
+    /// Competitive evacuees with a sub-step left push the cooperative neighbour closest to the exit
+    /// with the probability of the push policy, spending the sub-step on taking its cell.
+    /// The pushed evacuee is shoved into a free cell around it,
+    /// or swaps places with the pusher if there is none, and learns from a negative stimulus
+    fn push_evacuees(
+        &mut self,
+        evacuees: &mut [EvacueeCell],
+        evacuee_agent: &EvacueeAgent,
+        rng: &mut impl RngCore,
+    ) {
+        let Some(policy) = self.push_policy else {
+            return;
+        };
+        let mut occupied: HashMap<Loc, usize> = evacuees
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (Loc(e.x, e.y), i))
+            .collect();
+        let mut involved = HashSet::new();
+        let mut order = (0..evacuees.len()).collect_vec();
+        order.shuffle(rng);
+        for i in order {
+            let origin = Loc(evacuees[i].x, evacuees[i].y);
+            if involved.contains(&i)
+                || evacuees[i].strategy != Strategy::Competitive
+                || evacuees[i].behaviour == Behaviour::Firefighter
+                || !evacuees[i].can_move()
+                || !rng.gen_bool(policy.probability.clamp(0., 1.) as f64)
+            {
+                continue;
+            }
+            let pushable = |loc: &Loc| {
+                occupied.get(loc).copied().filter(|j| {
+                    !involved.contains(j)
+                        && evacuees[*j].strategy == Strategy::Cooperative
+                        && evacuees[*j].behaviour != Behaviour::Firefighter
+                })
+            };
+            let Some((target, j)) = push_target(
+                origin,
+                self.neighbourhood.neighbours(&origin).map(|(loc, _)| loc),
+                pushable,
+                |loc| self.static_influence.static_influence(&(*loc).into()),
+            ) else {
+                continue;
+            };
+            let free = self
+                .available_cells(target.0, target.1, |loc| !occupied.contains_key(loc))
+                .into_iter()
+                .map(|(loc, _)| loc)
+                .filter(|loc| !self.escape_handler.is_exit(loc))
+                .collect_vec();
+            let dest = push_destination(origin, &free, rng);
+            occupied.remove(&origin);
+            occupied.insert(target, i);
+            occupied.insert(dest, j);
+            involved.insert(i);
+            involved.insert(j);
+            evacuees[i].x = target.0;
+            evacuees[i].y = target.1;
+            evacuees[i].spend_substep();
+
+            let victim = &mut evacuees[j];
+            victim.x = dest.0;
+            victim.y = dest.1;
+            if victim.behaviour.adaptive() {
+                if self.strategy_representation == StrategyRepresentation::Continuous {
+                    evacuee_agent.calculate_level(victim, policy.stimulus);
+                } else {
+                    let peers = self.neighbour_payoffs(dest.0, dest.1);
+                    evacuee_agent.calculate_strategies(victim, rng, policy.stimulus, &peers);
+                }
+            }
+            if !victim.injured && rng.gen::<f32>() < policy.injury_risk {
+                victim.injure();
+                self.death_handler
+                    .update_injury(dest.into(), DeathCause::Push);
+                self.output_vars.push_injuries += 1;
+            }
+            self.output_vars.pushes += 1;
+        }
+    }
+
+    /// Injure or incapacitate the evacuees under too much crowd pressure.
+    /// Every evacuee whose best neighbouring cell towards the exit is occupied presses against its occupant,
+    /// incapacitated evacuees are removed and handed to the death handler
+    fn crush_evacuees(&mut self, evacuees: &mut Vec<EvacueeCell>) {
+        let Some(policy) = self.pressure_policy else {
+            return;
+        };
+        let occupied: HashSet<Loc> = evacuees.iter().map(|e| Loc(e.x, e.y)).collect();
+        let dist = |loc: Loc| self.static_influence.static_influence(&loc.into());
+        let mut pressers = evacuees
+            .iter()
+            .filter(|e| e.behaviour != Behaviour::Firefighter)
+            .filter_map(|e| {
+                let from = Loc(e.x, e.y);
+                let own = dist(from);
+                let (to, d) = self
+                    .neighbourhood
+                    .neighbours(&from)
+                    .filter(|(Loc(i, j), _)| {
+                        within_bounds(*i, self.dim.0 as i32) && within_bounds(*j, self.dim.1 as i32)
+                    })
+                    .map(|(loc, _)| (loc, dist(loc)))
+                    .min_by(|a, b| a.1.total_cmp(&b.1))?;
+                let presser = Presser {
+                    from,
+                    to,
+                    competitive: e.strategy == Strategy::Competitive,
+                };
+                (d < own && occupied.contains(&to)).then_some((own, presser))
+            })
+            .collect_vec();
+        // Pressure travels towards the exit
+        pressers.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let pressers = pressers.into_iter().map(|(_, p)| p).collect_vec();
+        let pressure = policy.pressure(&pressers);
+
+        let exits = self.escape_handler.exits();
+        let mut crushed = vec![];
+        for (i, e) in evacuees.iter_mut().enumerate() {
+            let loc = Int2D { x: e.x, y: e.y };
+            let Some(p) = pressure.get(&Loc(e.x, e.y)) else {
+                continue;
+            };
+            if e.behaviour == Behaviour::Firefighter {
+                continue;
+            }
+            match policy.outcome(*p) {
+                CrushOutcome::Unharmed => {}
+                CrushOutcome::Injured => {
+                    if !e.injured {
+                        e.injure();
+                        self.death_handler.update_injury(loc, DeathCause::Crush);
+                        self.output_vars.crush_injuries += 1;
+                    }
+                }
+                CrushOutcome::Incapacitated => {
+                    crushed.push(i);
+                    self.death_handler.update_death(loc, DeathCause::Crush);
+                    self.output_vars.crush_deaths += 1;
+                    if exits
+                        .iter()
+                        .any(|exit| distsq(&loc, &(*exit).into()) <= policy.exit_radius.powi(2))
+                    {
+                        self.output_vars.exit_crush += 1;
+                    }
+                }
+            }
+        }
+        for i in crushed.into_iter().rev() {
+            evacuees.remove(i);
+        }
+    }
+
+    /// Helped evacuees follow their helper, the ones whose helper escaped get out with it.
+    /// On a new tick cooperative evacuees offer help to a neighbour in need with their
+    /// cooperation probability scaled by the willingness of the policy
+    fn assist_evacuees(
+        &mut self,
+        evacuees: &mut Vec<EvacueeCell>,
+        rng: &mut impl RngCore,
+        new_tick: bool,
+    ) {
+        let Some(assistance) = self.assistance.as_mut() else {
+            return;
+        };
+        for released in assistance.follow(evacuees) {
+            let Some(i) = evacuees.iter().position(|e| e.id == released.helped) else {
+                continue;
+            };
+            let rescued = self
+                .escape_handler
+                .get_escaped()
+                .iter()
+                .any(|t| t.loc.id == released.helper);
+            if rescued {
+                let helped = evacuees.remove(i);
+                self.escape_handler.escaped(helped, self.step as usize);
+                assistance.rescues += 1;
+            }
+        }
+        if !new_tick {
+            return;
+        }
+        let Some(assistance) = self.assistance.as_mut() else {
+            return;
+        };
+        let occupied: HashMap<Loc, usize> = evacuees
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (Loc(e.x, e.y), i))
+            .collect();
+        let mut order = (0..evacuees.len()).collect_vec();
+        order.shuffle(rng);
+        for i in order {
+            let helper = evacuees[i];
+            if helper.strategy != Strategy::Cooperative
+                || helper.behaviour == Behaviour::Firefighter
+                || assistance.is_paired(helper.id)
+                || assistance.needs_help(&helper)
+            {
+                continue;
+            }
+            let Some(j) = Neighbourhood::Moore
+                .neighbours(&Loc(helper.x, helper.y))
+                .filter_map(|(loc, _)| occupied.get(&loc).copied())
+                .find(|j| {
+                    evacuees[*j].behaviour != Behaviour::Firefighter
+                        && assistance.needs_help(&evacuees[*j])
+                })
+            else {
+                continue;
+            };
+            if rng.gen::<f32>() < assistance.help_probability(&helper) {
+                let mut helped = evacuees[j];
+                assistance.pair(&mut evacuees[i], &mut helped);
+                evacuees[j] = helped;
+            }
+        }
+    }
+
+    /// Move the evacuees for a single tick.
+    /// Evacuees faster than one cell per tick take extra sub-steps, every sub-step follows the update scheme
+    pub fn evacuee_step(&mut self, evacuee_agent: &EvacueeAgent, rng: &mut impl RngCore) {
+        self.update_queues();
+        self.update_groups();
+        self.update_wardens();
+        run_substeps(|new_tick| {
+            let mut lp = match self.update_scheme {
+                UpdateScheme::Parallel => self.parallel_step(evacuee_agent, rng, new_tick),
+                _ => self.sequential_step(evacuee_agent, rng, new_tick),
+            };
+            if new_tick {
+                self.push_evacuees(&mut lp, evacuee_agent, rng);
+                self.crush_evacuees(&mut lp);
+            }
+            self.assist_evacuees(&mut lp, rng, new_tick);
+            if new_tick {
+                self.deploy_firefighters(&mut lp);
+                self.descend_stairs(&mut lp, rng);
+            }
+            let pending = lp.iter().any(|e| e.can_move());
+            for e in lp {
+                self.evac_grid
+                    .set_value_location(e, &Int2D { x: e.x, y: e.y })
+            }
+            if pending {
+                // Make the moves of this sub-step visible before the next one
+                self.evac_grid.lazy_update();
+            }
+            pending
+        });
     }
 
     // #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
@@ -487,6 +1622,17 @@ impl CellGrid {
     ///
     pub fn fire_step(&mut self, fire_agent: &mut impl Transition, rng: &mut impl RngCore) {
         // let mut updated = Vec::with_capacity((self.dim.0 * self.dim.1) as usize);
+        let suppressed = self
+            .firefighters
+            .as_mut()
+            .map_or_else(Vec::new, |f| f.take_suppressed());
+        for loc in suppressed {
+            let idx = (loc.0 * self.dim.1 as i32 + loc.1) as usize;
+            if fire_agent.handle_grid()[idx] == CellType::Fire {
+                fire_agent.handle_grid()[idx] = CellType::Empty;
+                self.fire_influence.on_suppressed(&loc);
+            }
+        }
         for idx in 0..(self.dim.0 * self.dim.1) as usize {
             let x = (idx as u32 / self.dim.1) as i32;
             let y = (idx as u32 % self.dim.1) as i32;
@@ -522,6 +1668,21 @@ impl CellGrid {
                 self.grid.set_value_location(cell, &loc);
             }
         }
+        if let Some(building) = self.building.as_mut() {
+            let ground = fire_agent.handle_grid().clone();
+            let ignited = building.fire_step(
+                &ground,
+                |cell, n| fire_agent.transition_with_number(cell, n),
+                rng,
+            );
+            // Fire coming down from the first floor
+            for loc in ignited {
+                let idx = (loc.0 * self.dim.1 as i32 + loc.1) as usize;
+                fire_agent.handle_grid()[idx] = CellType::Fire;
+                self.grid.set_value_location(CellType::Fire, &loc.into());
+                self.fire_influence.on_step(&loc);
+            }
+        }
     }
     // pub fn fire_step(&mut self, fire_agent: &impl Transition, rng: &mut impl RngCore) {
     //     // let mut updated = Vec::new();
@@ -561,6 +1722,7 @@ impl CellGrid {
 impl State for CellGrid {
     fn update(&mut self, step: u64) {
         self.step = step;
+        self.fire_influence.step = step;
         self.grid.lazy_update();
         self.evac_grid.lazy_update();
     }
@@ -590,8 +1752,6 @@ impl State for CellGrid {
     fn after_step(&mut self, schedule: &mut engine::schedule::Schedule) {
         use rand_distr::num_traits::Zero;
 
-        use crate::model::evacuee_mod::strategy::Strategy;
-
         plot!(
             "Escaped".to_owned(),
             "series".to_owned(),
@@ -612,7 +1772,7 @@ impl State for CellGrid {
             "AspirationArea".to_owned(),
             "series".to_owned(),
             self.fire_influence.fire_area as f64,
-            round(self.fire_influence.aspiration.calculate_asp(self.fire_influence.fire_area) as f64,3),
+            round(self.fire_influence.calculate_aspiration() as f64,3),
             csv : true
         );
 
@@ -628,12 +1788,229 @@ impl State for CellGrid {
             );
         }
 
+        for (series, count) in [
+            ("Blocked", self.output_vars.arguments_blocked),
+            ("Won", self.output_vars.arguments_won),
+            ("Injuries", self.output_vars.argument_injuries),
+        ] {
+            plot!(
+                "ArgumentOutcomeTime".to_owned(),
+                series.to_owned(),
+                schedule.step as f64,
+                count as f64,
+                csv : true
+            );
+        }
+
         let f = RefCell::new(vec![]);
 
+        let responders = RefCell::new(0usize);
         self.evac_grid.iter_values_unbuffered(|_, e| {
-            f.borrow_mut().push(*e);
+            if e.behaviour == Behaviour::Firefighter {
+                *responders.borrow_mut() += 1;
+            } else {
+                f.borrow_mut().push(*e);
+            }
         });
         let f = f.take();
+        if let Some(building) = self.building.as_ref() {
+            let remaining = building.remaining();
+            for (floor, val) in remaining.into_iter().enumerate() {
+                plot!(
+                    "Floors".to_owned(),
+                    format!("Floor {}", floor + 1),
+                    schedule.step as f64,
+                    val as f64,
+                    csv : true
+                );
+            }
+            plot!(
+                "Floors".to_owned(),
+                "Stairs".to_owned(),
+                schedule.step as f64,
+                building.in_stairs() as f64,
+                csv : true
+            );
+        }
+        if let Some(firefighters) = self.firefighters.as_ref() {
+            for (series, val) in [
+                ("Dispatched", firefighters.dispatched() as u64),
+                ("Inside", responders.take() as u64),
+                ("Suppressed", firefighters.suppressed() as u64),
+            ] {
+                plot!(
+                    "Firefighters".to_owned(),
+                    series.to_owned(),
+                    schedule.step as f64,
+                    val as f64,
+                    csv : true
+                );
+            }
+        }
+        if self.strategy_representation == StrategyRepresentation::Continuous {
+            let hist = level_histogram(f.iter().map(|e| e.coop_level));
+            for (i, count) in hist.into_iter().enumerate() {
+                plot!(
+                    "CoopLevelHistogram".to_owned(),
+                    format!("{:.1}", i as f32 / LEVEL_BINS as f32),
+                    schedule.step as f64,
+                    count as f64,
+                    csv : true
+                );
+            }
+        }
+        if !f.is_empty() {
+            let n = f.len() as f32;
+            let mean = f.iter().map(|e| e.aspiration).sum::<f32>() / n;
+            let var = f.iter().map(|e| (e.aspiration - mean).powi(2)).sum::<f32>() / n;
+            for (series, val) in [("Mean", mean), ("Std", var.sqrt())] {
+                plot!(
+                    "AgentAspiration".to_owned(),
+                    series.to_owned(),
+                    schedule.step as f64,
+                    round(val as f64, 3),
+                    csv : true
+                );
+            }
+        }
+        if let Some(queues) = self.exit_queues.as_ref() {
+            for exit in self.escape_handler.exits() {
+                plot!(
+                    "ExitQueue".to_owned(),
+                    format!("Length {exit:?}"),
+                    schedule.step as f64,
+                    queues.len(exit) as f64,
+                    csv : true
+                );
+                if let Some(wait) = queues.mean_wait(exit) {
+                    plot!(
+                        "QueueWait".to_owned(),
+                        format!("{exit:?}"),
+                        schedule.step as f64,
+                        round(wait, 3),
+                        csv : true
+                    );
+                }
+            }
+            plot!(
+                "ExitQueue".to_owned(),
+                "Jumps".to_owned(),
+                schedule.step as f64,
+                queues.jumps as f64,
+                csv : true
+            );
+        }
+        if self.push_policy.is_some() {
+            for (series, val) in [
+                ("Pushes", self.output_vars.pushes),
+                ("Injuries", self.output_vars.push_injuries),
+            ] {
+                plot!(
+                    "Pushing".to_owned(),
+                    series.to_owned(),
+                    schedule.step as f64,
+                    val as f64,
+                    csv : true
+                );
+            }
+        }
+        for cause in CAUSES {
+            plot!(
+                "DeathByCause".to_owned(),
+                format!("{cause:?}"),
+                schedule.step as f64,
+                self.death_handler.get_dead_by(cause) as f64,
+                csv : true
+            );
+            plot!(
+                "InjuryByCause".to_owned(),
+                format!("{cause:?}"),
+                schedule.step as f64,
+                self.death_handler.get_injured_by(cause) as f64,
+                csv : true
+            );
+        }
+        if self.pressure_policy.is_some() {
+            for (series, val) in [
+                ("Deaths", self.output_vars.crush_deaths),
+                ("Exit", self.output_vars.exit_crush),
+                ("Injuries", self.output_vars.crush_injuries),
+            ] {
+                plot!(
+                    "CrushCasualties".to_owned(),
+                    series.to_owned(),
+                    schedule.step as f64,
+                    val as f64,
+                    csv : true
+                );
+            }
+        }
+        if let Some(assistance) = self.assistance.as_ref() {
+            for (series, val) in [
+                ("Rescues", assistance.rescues),
+                ("Assisted", assistance.assisted() as u64),
+            ] {
+                plot!(
+                    "Assistance".to_owned(),
+                    series.to_owned(),
+                    schedule.step as f64,
+                    val as f64,
+                    csv : true
+                );
+            }
+        }
+        if let Some(wardens) = self.wardens.as_ref() {
+            for (series, val) in [
+                ("Wardens", wardens.count() as u64),
+                ("Guided", wardens.guided() as u64),
+                ("Persuaded", self.output_vars.persuaded),
+            ] {
+                plot!(
+                    "WardenGuidance".to_owned(),
+                    series.to_owned(),
+                    schedule.step as f64,
+                    val as f64,
+                    csv : true
+                );
+            }
+        }
+        let escaped = self.escape_handler.get_escaped();
+        if let Some(groups) = self.groups.as_ref() {
+            for (size, population) in groups.population_by_size() {
+                let survived = escaped
+                    .iter()
+                    .filter(|e| e.loc.group.map(|g| groups.size(g)) == Some(size))
+                    .count();
+                plot!(
+                    "SurvivalByGroupSize".to_owned(),
+                    format!("Size {size}"),
+                    schedule.step as f64,
+                    round(survived as f64 / population as f64, 3),
+                    csv : true
+                );
+            }
+            plot!(
+                "GroupWaiting".to_owned(),
+                "series".to_owned(),
+                schedule.step as f64,
+                groups.waiting() as f64,
+                csv : true
+            );
+        }
+        for b in BEHAVIOURS {
+            let population = self.population_by_behaviour[b.index()];
+            if population == 0 {
+                continue;
+            }
+            let survived = escaped.iter().filter(|e| e.loc.behaviour == b).count();
+            plot!(
+                "SurvivalByBehaviour".to_owned(),
+                format!("{b:?}"),
+                schedule.step as f64,
+                round(survived as f64 / population as f64, 3),
+                csv : true
+            );
+        }
         let total_num = f.len();
         let coops = f
             .iter()
@@ -687,17 +2064,16 @@ impl State for CellGrid {
                 csv : true
             );
         }
-        self.output_vars.per_case_ratio_1 = 0;
-        self.output_vars.per_case_ratio_2 = 0;
-        self.output_vars.per_case_ratio_3 = 0;
+        self.output_vars.reset();
     }
 
     #[cfg(any(feature = "bayesian", feature = "ga_search"))]
 
     fn end_condition(&mut self, _schedule: &mut krabmaga::engine::schedule::Schedule) -> bool {
+        let upper = self.building.as_ref().map_or(0, |b| b.population());
         self.fire_influence.fire_area == (self.dim.0 * self.dim.1) as usize
-            || self.initial_config.evac_num
-                == self.death_handler.get_dead() + self.escape_handler.get_escaped().len()
+            || self.initial_config.evac_num + upper
+                <= self.death_handler.get_dead() + self.escape_handler.get_escaped().len()
     }
 
     // Determine fire_out
@@ -709,9 +2085,25 @@ impl State for CellGrid {
         self.escape_handler.reset();
         self.grid = DenseNumberGrid2D::new(self.dim.0 as i32, self.dim.1 as i32);
         self.evac_grid = DenseNumberGrid2D::new(self.dim.0 as i32, self.dim.1 as i32);
-        self.output_vars.per_case_ratio_1 = 0;
-        self.output_vars.per_case_ratio_2 = 0;
-        self.output_vars.per_case_ratio_3 = 0;
+        self.output_vars.reset();
+        if let Some(reputation) = self.reputation.as_mut() {
+            reputation.reset();
+        }
+        if let Some(queues) = self.exit_queues.as_mut() {
+            queues.reset();
+        }
+        if let Some(assistance) = self.assistance.as_mut() {
+            assistance.reset();
+        }
+        if let Some(groups) = self.groups.as_mut() {
+            groups.reset();
+        }
+        if let Some(wardens) = self.wardens.as_mut() {
+            wardens.reset();
+        }
+        if let Some(building) = self.building.as_mut() {
+            building.reset();
+        }
     }
 
     #[cfg(any(feature = "visualization", feature = "visualization_wasm"))]
@@ -733,10 +2125,13 @@ impl State for CellGrid {
             (x as u32 * self.dim.1 + y as u32) as usize,
         );
 
+        let lc = self.initial_config.lc.unwrap_or_else(|| rng.gen());
+        let ld = self.initial_config.ld.unwrap_or_else(|| rng.gen());
         let evac_agent = EvacueeAgent {
             id: 2,
-            lc: self.initial_config.lc.unwrap_or_else(|| rng.gen()),
-            ld: self.initial_config.ld.unwrap_or_else(|| rng.gen()),
+            lc,
+            ld,
+            learning: self.initial_config.learning_rules(lc, ld),
         };
 
         // Update on the non visual feature does not copy between the state
@@ -769,10 +2164,13 @@ impl State for CellGrid {
             (x as u32 * self.dim.1 + y as u32) as usize,
         );
 
+        let lc = self.initial_config.lc.unwrap_or_else(|| rng.gen());
+        let ld = self.initial_config.ld.unwrap_or_else(|| rng.gen());
         let agent = EvacueeAgent {
             id: 2,
-            lc: self.initial_config.lc.unwrap_or_else(|| rng.gen()),
-            ld: self.initial_config.ld.unwrap_or_else(|| rng.gen()),
+            lc,
+            ld,
+            learning: self.initial_config.learning_rules(lc, ld),
         };
 
         // Update on the non visual feature does not copy between the state
@@ -867,7 +2265,133 @@ impl State for CellGrid {
                 "Time".to_owned(),
                 "GameRatio".to_owned(),
                 csv: true
-            )
+            );
+
+            addplot!(
+                "ArgumentOutcome".to_owned(),
+                "Time".to_owned(),
+                "Injuries".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "ArgumentOutcomeTime".to_owned(),
+                "Time".to_owned(),
+                "Arguments".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "DeathByCause".to_owned(),
+                "Time".to_owned(),
+                "Number of dead evacuees".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "InjuryByCause".to_owned(),
+                "Time".to_owned(),
+                "Number of injured evacuees".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "CrushCasualties".to_owned(),
+                "Time".to_owned(),
+                "Evacuees".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "Assistance".to_owned(),
+                "Time".to_owned(),
+                "Evacuees".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "Pushing".to_owned(),
+                "Time".to_owned(),
+                "Evacuees".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "ExitQueue".to_owned(),
+                "Time".to_owned(),
+                "Evacuees".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "QueueWait".to_owned(),
+                "Time".to_owned(),
+                "Mean waiting time".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "GroupCooperation".to_owned(),
+                "Group size".to_owned(),
+                "Cooperator share".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "AgentAspiration".to_owned(),
+                "Time".to_owned(),
+                "Own aspiration".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "SurvivalByGroupSize".to_owned(),
+                "Time".to_owned(),
+                "Escaped fraction".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "GroupWaiting".to_owned(),
+                "Time".to_owned(),
+                "Members waiting for a straggler".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "WardenGuidance".to_owned(),
+                "Time".to_owned(),
+                "Count".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "Firefighters".to_owned(),
+                "Time".to_owned(),
+                "Count".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "Floors".to_owned(),
+                "Time".to_owned(),
+                "Evacuees left".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "SurvivalByBehaviour".to_owned(),
+                "Time".to_owned(),
+                "Escaped fraction".to_owned(),
+                csv: true
+            );
+
+            addplot!(
+                "CoopLevelHistogram".to_owned(),
+                "Time".to_owned(),
+                "Evacuees per cooperation level".to_owned(),
+                csv: true
+            );
         }
     }
 }